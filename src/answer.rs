@@ -0,0 +1,84 @@
+//! Derives answers to security questions, random but recallable with the tool.
+//! The normalized question is folded into the derivation, so every question of a site gets its own answer
+//! and rewording the case, spacing or punctuation of a question keeps it.
+//! Uses the `generator::derive_bytes` labels `answer:words` and `answer:pronounceable`.
+//!
+//! # Examples
+//! ```
+//! use kg_passgen::answer::{generate_answer, normalize_question};
+//! use kg_passgen::config::Config;
+//! let config = Config::default();
+//!
+//! let answer = generate_answer("https://example.com", "What is your mother's maiden name?", "my_master_password", &config);
+//! assert_eq!(answer.split(' ').count(), 4);
+//! assert_eq!(answer, generate_answer("https://example.com", "what is your mothers  maiden name", "my_master_password", &config));
+//! assert_eq!(normalize_question(" What's your first pet's NAME? "), "whats your first pets name");
+//! ```
+
+use crate::config::Config;
+use crate::generator::derive_bytes;
+use crate::generator::pronounceable::syllables;
+use crate::username::{ADJECTIVES, NOUNS};
+
+/// Formats of the generated answer
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum AnswerStyle {
+    /// Four words, e.g. `amber falcon quiet river`, about 28 bits
+    #[default]
+    Words,
+    /// Two made-up words of three syllables, e.g. `kabote mirusa`, about 38 bits
+    Pronounceable,
+}
+
+/// Lowercases the question and keeps only letters, digits and single spaces
+pub fn normalize_question(question: &str) -> String {
+    let cleaned: String = question.chars().filter(|c| c.is_alphanumeric() || c.is_whitespace()).flat_map(char::to_lowercase).collect();
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Generates a word-based answer to the question for the service URL
+pub fn generate_answer(url: &str, question: &str, master_password: &str, config: &Config) -> String {
+    generate_answer_with_style(url, question, master_password, AnswerStyle::Words, config)
+}
+
+/// Generates an answer to the question for the service URL in the selected style
+pub fn generate_answer_with_style(url: &str, question: &str, master_password: &str, style: AnswerStyle, config: &Config) -> String {
+    let host = crate::url_helper::get_config_host(url, config);
+    // The normalized question has no colon, so the host and question cannot run into each other
+    let site = format!("{}:{}", host, normalize_question(question));
+
+    match style {
+        AnswerStyle::Words => {
+            let bytes = derive_bytes("answer:words", master_password, &site);
+            format!("{} {} {} {}",
+                ADJECTIVES[bytes[0] as usize % ADJECTIVES.len()], NOUNS[bytes[1] as usize % NOUNS.len()],
+                ADJECTIVES[bytes[2] as usize % ADJECTIVES.len()], NOUNS[bytes[3] as usize % NOUNS.len()])
+        },
+        AnswerStyle::Pronounceable => {
+            let bytes = derive_bytes("answer:pronounceable", master_password, &site);
+            format!("{} {}", syllables(&bytes[0..6]), syllables(&bytes[6..12]))
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answers_depend_on_question_and_host() {
+        let config = Config::default();
+        let answer = generate_answer("https://example.com", "Mother's maiden name?", "my_master_password", &config);
+        assert_ne!(answer, generate_answer("https://example.com", "First pet?", "my_master_password", &config));
+        assert_ne!(answer, generate_answer("https://example.org", "Mother's maiden name?", "my_master_password", &config));
+        assert_eq!(answer, generate_answer("https://www.example.com/login", "MOTHERS MAIDEN NAME", "my_master_password", &config));
+    }
+
+    #[test]
+    fn test_pronounceable_answer_layout() {
+        let answer = generate_answer_with_style("https://example.com", "First pet?", "my_master_password", AnswerStyle::Pronounceable, &Config::default());
+        let words: Vec<&str> = answer.split(' ').collect();
+        assert_eq!(words.len(), 2);
+        assert!(words.iter().all(|word| word.len() == 6 && word.chars().all(|c| c.is_ascii_lowercase())));
+    }
+}
@@ -0,0 +1,245 @@
+//! Offline checks of passwords against a locally downloaded Have I Been Pwned dump.
+//! The dump is the "ordered by hash" SHA-1 file with one `HASH:COUNT` line per password.
+//! It is searched with a binary search over the file, so it never has to fit in memory.
+//! A compact Bloom filter can be built from the dump for faster checks with rare false positives.
+//!
+//! # Examples
+//! ```
+//! use std::io::Cursor;
+//! use kg_passgen::breach::{find_in_dump, sha1_hex};
+//! assert_eq!(sha1_hex("password"), "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8");
+//!
+//! let dump = "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:52256179\r\n7C4A8D09CA3762AF61E59520943DC26494F8941B:3861493\r\n";
+//! assert_eq!(find_in_dump(&mut Cursor::new(dump), "password").unwrap(), Some(52256179));
+//! assert_eq!(find_in_dump(&mut Cursor::new(dump), "correct horse battery staple").unwrap(), None);
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use sha1::{Digest, Sha1};
+
+const FILTER_MAGIC: &[u8; 8] = b"KGPGBLM1";
+
+/// Result of a breach check
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BreachCheck {
+    NotFound,
+    /// Found in the dump, with the number of times it was seen in breaches
+    Found(u64),
+    /// Found in the filter, which has rare false positives
+    ProbablyFound,
+}
+
+/// Returns the uppercase hex SHA-1 of the password, as used in the dump
+pub fn sha1_hex(password: &str) -> String {
+    Sha1::digest(password.as_bytes()).iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+/// Reads the line starting at the position, without the line ending
+fn read_line_at<R: BufRead + Seek>(reader: &mut R, position: u64) -> io::Result<Vec<u8>> {
+    reader.seek(SeekFrom::Start(position))?;
+    let mut line = Vec::new();
+    reader.read_until(b'\n', &mut line)?;
+    Ok(line)
+}
+
+/// Returns the position of the first line starting at or after the position
+fn next_line_start<R: BufRead + Seek>(reader: &mut R, position: u64) -> io::Result<u64> {
+    if position == 0 {
+        return Ok(0);
+    }
+    Ok(position - 1 + read_line_at(reader, position - 1)?.len() as u64)
+}
+
+/// Searches the sorted dump for the password and returns its breach count.
+/// Lines without a count are reported with a count of 1.
+pub fn find_in_dump<R: Read + Seek>(dump: &mut R, password: &str) -> io::Result<Option<u64>> {
+    let target = sha1_hex(password);
+    let mut reader = BufReader::new(dump);
+    let (mut low, mut high) = (0, reader.seek(SeekFrom::End(0))?);
+
+    while low < high {
+        let start = next_line_start(&mut reader, low + (high - low) / 2)?;
+        if start >= high {
+            high = low + (high - low) / 2;
+            continue;
+        }
+
+        let line = read_line_at(&mut reader, start)?;
+        let text = String::from_utf8_lossy(&line);
+        let (hash, count) = text.trim_end().split_once(':').unwrap_or((text.trim_end(), "1"));
+        match hash.to_ascii_uppercase().as_str().cmp(target.as_str()) {
+            std::cmp::Ordering::Equal => return Ok(Some(count.trim().parse().unwrap_or(1))),
+            std::cmp::Ordering::Less => low = start + line.len() as u64,
+            std::cmp::Ordering::Greater => high = start,
+        }
+    }
+    Ok(None)
+}
+
+/// Bloom filter of the SHA-1 hashes of a dump.
+/// The bit positions come from the hash itself with double hashing, as SHA-1 is already uniform.
+#[derive(Debug, PartialEq, Clone)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    hashes: u32,
+}
+
+impl BloomFilter {
+    /// Creates an empty filter sized for the number of items and false positive rate
+    pub fn new(items: u64, false_positive_rate: f64) -> Self {
+        let items = items.max(1) as f64;
+        let bit_count = (-items * false_positive_rate.ln() / 2f64.ln().powi(2)).ceil().max(64.0);
+        let hashes = (bit_count / items * 2f64.ln()).round().clamp(1.0, 32.0) as u32;
+        BloomFilter { bits: vec![0; (bit_count as usize).div_ceil(64)], hashes }
+    }
+
+    fn positions(&self, hash: &[u8; 20]) -> impl Iterator<Item = usize> + '_ {
+        let first = u64::from_le_bytes(hash[0..8].try_into().unwrap());
+        let second = u64::from_le_bytes(hash[8..16].try_into().unwrap()) | 1;
+        let bit_count = self.bits.len() as u64 * 64;
+        (0..self.hashes as u64).map(move |i| (first.wrapping_add(i.wrapping_mul(second)) % bit_count) as usize)
+    }
+
+    pub fn insert_hash(&mut self, hash: &[u8; 20]) {
+        for position in self.positions(hash).collect::<Vec<_>>() {
+            self.bits[position / 64] |= 1 << (position % 64);
+        }
+    }
+
+    pub fn contains_hash(&self, hash: &[u8; 20]) -> bool {
+        self.positions(hash).all(|position| self.bits[position / 64] & (1 << (position % 64)) != 0)
+    }
+
+    /// Checks whether the password is probably in the filter
+    pub fn contains(&self, password: &str) -> bool {
+        self.contains_hash(&Sha1::digest(password.as_bytes()).into())
+    }
+
+    /// Builds a filter from the lines of a dump, invalid lines are skipped
+    pub fn from_dump<R: BufRead>(dump: R, items: u64, false_positive_rate: f64) -> io::Result<Self> {
+        let mut filter = BloomFilter::new(items, false_positive_rate);
+        for line in dump.lines() {
+            let line = line?;
+            let hex = line.split(':').next().unwrap_or_default().trim();
+            let bytes: Option<Vec<u8>> = (0..hex.len()).step_by(2).map(|i| hex.get(i..i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok())).collect();
+            if let Some(Ok(hash)) = bytes.map(<[u8; 20]>::try_from) {
+                filter.insert_hash(&hash);
+            }
+        }
+        Ok(filter)
+    }
+
+    /// Writes the filter in its binary format
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(FILTER_MAGIC)?;
+        writer.write_all(&self.hashes.to_le_bytes())?;
+        writer.write_all(&(self.bits.len() as u64).to_le_bytes())?;
+        for word in &self.bits {
+            writer.write_all(&word.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Reads a filter written with `write_to`
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != FILTER_MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a breach filter file"));
+        }
+        let mut hashes = [0u8; 4];
+        let mut words = [0u8; 8];
+        reader.read_exact(&mut hashes)?;
+        reader.read_exact(&mut words)?;
+
+        let words = u64::from_le_bytes(words) as usize;
+        let mut bytes = Vec::new();
+        reader.take(words as u64 * 8).read_to_end(&mut bytes)?;
+        if bytes.len() != words * 8 || words == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Truncated breach filter file"));
+        }
+        let bits = bytes.chunks_exact(8).map(|word| u64::from_le_bytes(word.try_into().unwrap())).collect();
+        Ok(BloomFilter { bits, hashes: u32::from_le_bytes(hashes) })
+    }
+}
+
+/// Where breached passwords are looked up
+#[derive(Debug, PartialEq, Clone)]
+pub enum BreachSource {
+    /// A sorted dump file, searched on every check
+    Dump(PathBuf),
+    /// A filter loaded in memory
+    Filter(BloomFilter),
+}
+
+impl BreachSource {
+    /// Loads a filter file written with `BloomFilter::write_to`
+    pub fn load_filter(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(BreachSource::Filter(BloomFilter::read_from(&mut BufReader::new(File::open(path)?))?))
+    }
+
+    pub fn check(&self, password: &str) -> io::Result<BreachCheck> {
+        match self {
+            BreachSource::Dump(path) => Ok(match find_in_dump(&mut File::open(path)?, password)? {
+                Some(count) => BreachCheck::Found(count),
+                None => BreachCheck::NotFound,
+            }),
+            BreachSource::Filter(filter) if filter.contains(password) => Ok(BreachCheck::ProbablyFound),
+            BreachSource::Filter(_) => Ok(BreachCheck::NotFound),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn dump(passwords: &[&str]) -> String {
+        let mut lines: Vec<String> = passwords.iter().enumerate().map(|(i, p)| format!("{}:{}", sha1_hex(p), i + 1)).collect();
+        lines.sort();
+        lines.join("\r\n") + "\r\n"
+    }
+
+    #[test]
+    fn test_find_in_dump_finds_every_line() {
+        let passwords: Vec<String> = (0..200).map(|i| format!("password{}", i)).collect();
+        let references: Vec<&str> = passwords.iter().map(String::as_str).collect();
+        let dump = dump(&references);
+
+        for (i, password) in references.iter().enumerate() {
+            assert_eq!(find_in_dump(&mut Cursor::new(&dump), password).unwrap(), Some(i as u64 + 1), "{}", password);
+        }
+        for missing in ["", "password200", "PASSWORD1", "correct horse battery staple"] {
+            assert_eq!(find_in_dump(&mut Cursor::new(&dump), missing).unwrap(), None, "{}", missing);
+        }
+    }
+
+    #[test]
+    fn test_find_in_dump_handles_small_and_lowercase_files() {
+        assert_eq!(find_in_dump(&mut Cursor::new(""), "password").unwrap(), None);
+        let lowercase = format!("{}\n", sha1_hex("hunter2").to_lowercase());
+        assert_eq!(find_in_dump(&mut Cursor::new(lowercase.as_str()), "hunter2").unwrap(), Some(1));
+        let no_trailing_newline = format!("{}:7", sha1_hex("hunter2"));
+        assert_eq!(find_in_dump(&mut Cursor::new(no_trailing_newline.as_str()), "hunter2").unwrap(), Some(7));
+    }
+
+    #[test]
+    fn test_bloom_filter_round_trip() {
+        let passwords: Vec<String> = (0..1000).map(|i| format!("breached{}", i)).collect();
+        let references: Vec<&str> = passwords.iter().map(String::as_str).collect();
+        let filter = BloomFilter::from_dump(Cursor::new(dump(&references)), 1000, 0.001).unwrap();
+
+        let mut bytes = Vec::new();
+        filter.write_to(&mut bytes).unwrap();
+        let filter = BloomFilter::read_from(&mut Cursor::new(bytes)).unwrap();
+
+        assert!(references.iter().all(|password| filter.contains(password)));
+        let false_positives = (0..1000).filter(|i| filter.contains(&format!("safe{}", i))).count();
+        assert!(false_positives < 10, "{}", false_positives);
+        assert!(BloomFilter::read_from(&mut Cursor::new(b"not a filter".to_vec())).is_err());
+    }
+}
@@ -3,6 +3,15 @@
 //! Also includes default configurations for KGPG and SGP.
 //! When using the GeneratorType::KGPG, it adds an extra security layer 
 //! by making sure that it includes symbols in the generated passwords.
+//! GeneratorType::LessPass carries a LessPassProfile and reproduces LessPass passwords.
+//! GeneratorType::Spectre carries a SpectreProfile and reproduces Spectre (Master Password) passwords.
+//! GeneratorType::PasswordMaker carries a PasswordMakerProfile and reproduces PasswordMaker Pro passwords.
+//! GeneratorType::Pronounceable carries a PronounceableProfile and builds consonant-vowel syllables from the hops.
+//! Setting include_port gives different passwords to services running on different ports of the same host.
+//! Setting equivalences maps every domain of a shared-login group to one canonical host.
+//! Setting apps maps app package names to web domains in addition to the built-in mappings.
+//! Setting template gives hop based generators a fixed format such as `AAAA-9999-aaaa`, see `template`.
+//! Setting suffix_list selects the public suffix list, suffix_list_version records the version the profile was made with.
 //! 
 //! # Examples
 //! ```
@@ -32,6 +41,10 @@
 //! assert_eq!(sgp_config.length, 15);
 //! assert_eq!(sgp_config.hops, 15);
 //! ```
+use crate::suffix_list::SuffixList;
+use crate::template::Template;
+use crate::url_helper::{AppTable, EquivalenceTable, UrlComponents};
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum HashAlgorithm {
     SHA512,
@@ -42,6 +55,190 @@ pub enum HashAlgorithm {
 pub enum GeneratorType {
     KGPG,
     SGP,
+    LessPass(LessPassProfile),
+    Spectre(SpectreProfile),
+    PasswordMaker(PasswordMakerProfile),
+    Pronounceable(PronounceableProfile),
+}
+
+/// Profile fields used by the LessPass generator.
+/// The password length is taken from `Config::length` and must be between 5 and 35.
+/// `Config::hash_algorithm` and `Config::hops` are ignored since LessPass always uses PBKDF2-SHA256.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LessPassProfile {
+    pub login: String,
+    pub counter: u32,
+    pub lowercase: bool,
+    pub uppercase: bool,
+    pub digits: bool,
+    pub symbols: bool,
+}
+
+impl Default for LessPassProfile {
+    fn default() -> Self {
+        LessPassProfile::DEFAULT
+    }
+}
+
+impl LessPassProfile {
+    pub const DEFAULT: LessPassProfile = LessPassProfile {
+        login: String::new(),
+        counter: 1,
+        lowercase: true,
+        uppercase: true,
+        digits: true,
+        symbols: true,
+    };
+
+    pub fn with_login(mut self, login: &str) -> Self { self.login = login.to_string() ; self }
+    pub fn with_counter(mut self, counter: u32) -> Self { self.counter = counter ; self }
+    pub fn with_lowercase(mut self, lowercase: bool) -> Self { self.lowercase = lowercase ; self }
+    pub fn with_uppercase(mut self, uppercase: bool) -> Self { self.uppercase = uppercase ; self }
+    pub fn with_digits(mut self, digits: bool) -> Self { self.digits = digits ; self }
+    pub fn with_symbols(mut self, symbols: bool) -> Self { self.symbols = symbols ; self }
+}
+
+/// Password templates of the Spectre (Master Password) algorithm.
+/// Each template class defines the length and the characters of the generated password.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SpectreTemplate {
+    Maximum,
+    Long,
+    Medium,
+    Short,
+    Basic,
+    PIN,
+    Name,
+    Phrase,
+}
+
+/// Profile fields used by the Spectre generator.
+/// `Config::length`, `Config::hash_algorithm` and `Config::hops` are ignored since the template defines the output.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SpectreProfile {
+    pub full_name: String,
+    pub counter: u32,
+    pub template: SpectreTemplate,
+}
+
+impl Default for SpectreProfile {
+    fn default() -> Self {
+        SpectreProfile::DEFAULT
+    }
+}
+
+impl SpectreProfile {
+    pub const DEFAULT: SpectreProfile = SpectreProfile {
+        full_name: String::new(),
+        counter: 1,
+        template: SpectreTemplate::Long,
+    };
+
+    pub fn with_full_name(mut self, full_name: &str) -> Self { self.full_name = full_name.to_string() ; self }
+    pub fn with_counter(mut self, counter: u32) -> Self { self.counter = counter ; self }
+    pub fn with_template(mut self, template: SpectreTemplate) -> Self { self.template = template ; self }
+}
+
+/// Hash algorithms supported by the PasswordMaker generator
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PasswordMakerAlgorithm {
+    MD4,
+    MD5,
+    SHA1,
+    SHA256,
+    RIPEMD160,
+}
+
+/// When the PasswordMaker l33t replacements are applied
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LeetMode {
+    NotAtAll,
+    BeforeHashing,
+    AfterHashing,
+    Both,
+}
+
+/// Profile fields used by the PasswordMaker generator.
+/// The password length is taken from `Config::length`, and the URL components replace `Config::strip_subdomain`.
+/// `Config::hash_algorithm` and `Config::hops` are ignored.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PasswordMakerProfile {
+    pub algorithm: PasswordMakerAlgorithm,
+    pub hmac: bool,
+    pub characters: String,
+    pub url_components: UrlComponents,
+    pub username: String,
+    pub modifier: String,
+    pub prefix: String,
+    pub suffix: String,
+    pub leet_mode: LeetMode,
+    pub leet_level: u8,
+}
+
+impl Default for PasswordMakerProfile {
+    fn default() -> Self {
+        PasswordMakerProfile {
+            characters: PasswordMakerProfile::DEFAULT_CHARACTERS.to_string(),
+            ..PasswordMakerProfile::DEFAULT
+        }
+    }
+}
+
+impl PasswordMakerProfile {
+    pub const DEFAULT_CHARACTERS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789`~!@#$%^&*()_-+={}|[]\\:\";'<>?,./";
+
+    /// PasswordMaker Pro defaults. The character set is empty here since it cannot be built in a const,
+    /// an empty character set falls back to `DEFAULT_CHARACTERS` when generating.
+    pub const DEFAULT: PasswordMakerProfile = PasswordMakerProfile {
+        algorithm: PasswordMakerAlgorithm::MD5,
+        hmac: false,
+        characters: String::new(),
+        url_components: UrlComponents::DOMAIN,
+        username: String::new(),
+        modifier: String::new(),
+        prefix: String::new(),
+        suffix: String::new(),
+        leet_mode: LeetMode::NotAtAll,
+        leet_level: 1,
+    };
+
+    pub fn with_algorithm(mut self, algorithm: PasswordMakerAlgorithm) -> Self { self.algorithm = algorithm ; self }
+    pub fn with_hmac(mut self, hmac: bool) -> Self { self.hmac = hmac ; self }
+    pub fn with_characters(mut self, characters: &str) -> Self { self.characters = characters.to_string() ; self }
+    pub fn with_url_components(mut self, url_components: UrlComponents) -> Self { self.url_components = url_components ; self }
+    pub fn with_username(mut self, username: &str) -> Self { self.username = username.to_string() ; self }
+    pub fn with_modifier(mut self, modifier: &str) -> Self { self.modifier = modifier.to_string() ; self }
+    pub fn with_prefix(mut self, prefix: &str) -> Self { self.prefix = prefix.to_string() ; self }
+    pub fn with_suffix(mut self, suffix: &str) -> Self { self.suffix = suffix.to_string() ; self }
+    pub fn with_leet(mut self, leet_mode: LeetMode, leet_level: u8) -> Self { self.leet_mode = leet_mode ; self.leet_level = leet_level ; self }
+}
+
+/// Profile fields used by the Pronounceable generator.
+/// The password is `Config::length` characters long: syllables followed by the digits and the symbol,
+/// so at least two characters must be left for the syllables.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PronounceableProfile {
+    pub capitalize: bool,
+    pub digits: u8,
+    pub symbol: bool,
+}
+
+impl Default for PronounceableProfile {
+    fn default() -> Self {
+        PronounceableProfile::DEFAULT
+    }
+}
+
+impl PronounceableProfile {
+    pub const DEFAULT: PronounceableProfile = PronounceableProfile {
+        capitalize: true,
+        digits: 2,
+        symbol: true,
+    };
+
+    pub fn with_capitalize(mut self, capitalize: bool) -> Self { self.capitalize = capitalize ; self }
+    pub fn with_digits(mut self, digits: u8) -> Self { self.digits = digits ; self }
+    pub fn with_symbol(mut self, symbol: bool) -> Self { self.symbol = symbol ; self }
 }
 
 #[derive(Debug)]
@@ -51,6 +248,13 @@ pub struct Config {
     pub hash_algorithm: HashAlgorithm,
     pub length: u8,
     pub hops: u8,
+    pub include_port: bool,
+    pub equivalences: EquivalenceTable,
+    pub apps: AppTable,
+    pub suffix_list: SuffixList,
+    pub suffix_list_version: String,
+    /// Format of the password, `length` is ignored when set. LessPass, Spectre and PasswordMaker ignore it.
+    pub template: Option<Template>,
 }
 
 impl Default for Config {
@@ -61,6 +265,12 @@ impl Default for Config {
             hash_algorithm: HashAlgorithm::SHA512,
             length: 15,
             hops: 15,
+            include_port: false,
+            equivalences: EquivalenceTable::EMPTY,
+            apps: AppTable::EMPTY,
+            suffix_list: SuffixList::Builtin,
+            suffix_list_version: String::new(),
+            template: None,
         }
     }
 }
@@ -72,6 +282,12 @@ impl Config {
         hash_algorithm: HashAlgorithm::SHA512,
         length: 15,
         hops: 15,
+        include_port: false,
+        equivalences: EquivalenceTable::EMPTY,
+        apps: AppTable::EMPTY,
+        suffix_list: SuffixList::Builtin,
+        suffix_list_version: String::new(),
+        template: None,
     };
 
     pub const SGP: Config = Config {
@@ -80,6 +296,74 @@ impl Config {
         hash_algorithm: HashAlgorithm::SHA512,
         length: 10,
         hops: 10,
+        include_port: false,
+        equivalences: EquivalenceTable::EMPTY,
+        apps: AppTable::EMPTY,
+        suffix_list: SuffixList::Builtin,
+        suffix_list_version: String::new(),
+        template: None,
+    };
+
+    /// Matches the LessPass defaults: full hostname, 16 characters, all character sets and counter 1.
+    pub const LESSPASS: Config = Config {
+        generator_type: GeneratorType::LessPass(LessPassProfile::DEFAULT),
+        strip_subdomain: false,
+        hash_algorithm: HashAlgorithm::SHA512,
+        length: 16,
+        hops: 1,
+        include_port: false,
+        equivalences: EquivalenceTable::EMPTY,
+        apps: AppTable::EMPTY,
+        suffix_list: SuffixList::Builtin,
+        suffix_list_version: String::new(),
+        template: None,
+    };
+
+    /// Matches the Spectre defaults: registrable domain, Long template and counter 1.
+    /// The full name must be set on the profile before generating.
+    pub const SPECTRE: Config = Config {
+        generator_type: GeneratorType::Spectre(SpectreProfile::DEFAULT),
+        strip_subdomain: true,
+        hash_algorithm: HashAlgorithm::SHA512,
+        length: 14,
+        hops: 1,
+        include_port: false,
+        equivalences: EquivalenceTable::EMPTY,
+        apps: AppTable::EMPTY,
+        suffix_list: SuffixList::Builtin,
+        suffix_list_version: String::new(),
+        template: None,
+    };
+
+    /// Matches the PasswordMaker Pro defaults: MD5, domain only and 8 characters.
+    pub const PASSWORDMAKER: Config = Config {
+        generator_type: GeneratorType::PasswordMaker(PasswordMakerProfile::DEFAULT),
+        strip_subdomain: true,
+        hash_algorithm: HashAlgorithm::SHA512,
+        length: 8,
+        hops: 1,
+        include_port: false,
+        equivalences: EquivalenceTable::EMPTY,
+        apps: AppTable::EMPTY,
+        suffix_list: SuffixList::Builtin,
+        suffix_list_version: String::new(),
+        template: None,
+    };
+
+    /// Capitalized syllables with two digits and a symbol, e.g. `Pakuzoheligag14$`, for passwords typed on a phone or TV.
+    /// Syllables carry fewer bits per character, 16 characters give about 51 bits.
+    pub const PRONOUNCEABLE: Config = Config {
+        generator_type: GeneratorType::Pronounceable(PronounceableProfile::DEFAULT),
+        strip_subdomain: true,
+        hash_algorithm: HashAlgorithm::SHA512,
+        length: 16,
+        hops: 15,
+        include_port: false,
+        equivalences: EquivalenceTable::EMPTY,
+        apps: AppTable::EMPTY,
+        suffix_list: SuffixList::Builtin,
+        suffix_list_version: String::new(),
+        template: None,
     };
 
     pub fn with_strip_subdomain(mut self, strip: bool) -> Self { self.strip_subdomain = strip ; self }
@@ -87,6 +371,12 @@ impl Config {
     pub fn with_length(mut self, length: u8) -> Self { self.length = length ; self }
     pub fn with_hops(mut self, hops: u8) -> Self { self.hops = hops ; self }
     pub fn with_generator_type(mut self, generator_type: GeneratorType) -> Self { self.generator_type = generator_type ; self }
+    pub fn with_include_port(mut self, include_port: bool) -> Self { self.include_port = include_port ; self }
+    pub fn with_equivalences(mut self, equivalences: EquivalenceTable) -> Self { self.equivalences = equivalences ; self }
+    pub fn with_apps(mut self, apps: AppTable) -> Self { self.apps = apps ; self }
+    pub fn with_suffix_list(mut self, suffix_list: SuffixList) -> Self { self.suffix_list = suffix_list ; self }
+    pub fn with_suffix_list_version(mut self, version: &str) -> Self { self.suffix_list_version = version.to_string() ; self }
+    pub fn with_template(mut self, template: Template) -> Self { self.template = Some(template) ; self }
 }
 
 #[cfg(test)]
@@ -101,6 +391,12 @@ mod tests {
         assert_eq!(config.hash_algorithm, HashAlgorithm::SHA512);
         assert_eq!(config.length, 15);
         assert_eq!(config.hops, 15);
+        assert!(!config.include_port);
+        assert_eq!(config.equivalences, EquivalenceTable::EMPTY);
+        assert_eq!(config.apps, AppTable::EMPTY);
+        assert_eq!(config.suffix_list, SuffixList::Builtin);
+        assert!(config.suffix_list_version.is_empty());
+        assert_eq!(config.template, None);
     }
 
     #[test]
@@ -130,12 +426,83 @@ mod tests {
             .with_hash_algorithm(HashAlgorithm::SHA512)
             .with_length(20)
             .with_hops(5)
-            .with_generator_type(GeneratorType::KGPG);
+            .with_generator_type(GeneratorType::KGPG)
+            .with_include_port(true);
 
         assert_eq!(config.generator_type, GeneratorType::KGPG);
+        assert!(config.include_port);
         assert!(!config.strip_subdomain);
         assert_eq!(config.hash_algorithm, HashAlgorithm::SHA512);
         assert_eq!(config.length, 20);
         assert_eq!(config.hops, 5);
     }
+
+    #[test]
+    fn test_lesspass_config() {
+        let config = Config::LESSPASS;
+        assert_eq!(config.generator_type, GeneratorType::LessPass(LessPassProfile::default()));
+        assert!(!config.strip_subdomain);
+        assert_eq!(config.length, 16);
+    }
+
+    #[test]
+    fn test_lesspass_profile_builder_methods() {
+        let profile = LessPassProfile::default()
+            .with_login("contact@example.org")
+            .with_counter(2)
+            .with_symbols(false);
+
+        assert_eq!(profile.login, "contact@example.org");
+        assert_eq!(profile.counter, 2);
+        assert!(profile.lowercase && profile.uppercase && profile.digits);
+        assert!(!profile.symbols);
+    }
+
+    #[test]
+    fn test_spectre_config() {
+        let config = Config::SPECTRE;
+        assert_eq!(config.generator_type, GeneratorType::Spectre(SpectreProfile::default()));
+        assert!(config.strip_subdomain);
+
+        let profile = SpectreProfile::default()
+            .with_full_name("Robert Lee Mitchell")
+            .with_counter(3)
+            .with_template(SpectreTemplate::PIN);
+        assert_eq!(profile.full_name, "Robert Lee Mitchell");
+        assert_eq!(profile.counter, 3);
+        assert_eq!(profile.template, SpectreTemplate::PIN);
+    }
+
+    #[test]
+    fn test_pronounceable_config() {
+        let config = Config::PRONOUNCEABLE;
+        assert_eq!(config.generator_type, GeneratorType::Pronounceable(PronounceableProfile::default()));
+        assert_eq!((config.length, config.hops), (16, 15));
+
+        let profile = PronounceableProfile::default().with_capitalize(false).with_digits(4).with_symbol(false);
+        assert!(!profile.capitalize && !profile.symbol);
+        assert_eq!(profile.digits, 4);
+    }
+
+    #[test]
+    fn test_passwordmaker_profile_builder_methods() {
+        let profile = PasswordMakerProfile::default()
+            .with_algorithm(PasswordMakerAlgorithm::SHA256)
+            .with_hmac(true)
+            .with_characters("abc")
+            .with_username("user")
+            .with_modifier("1")
+            .with_prefix("pre")
+            .with_suffix("suf")
+            .with_leet(LeetMode::Both, 3);
+
+        assert_eq!(profile.algorithm, PasswordMakerAlgorithm::SHA256);
+        assert!(profile.hmac);
+        assert_eq!(profile.characters, "abc");
+        assert_eq!(profile.url_components, UrlComponents::DOMAIN);
+        assert_eq!((profile.username.as_str(), profile.modifier.as_str()), ("user", "1"));
+        assert_eq!((profile.prefix.as_str(), profile.suffix.as_str()), ("pre", "suf"));
+        assert_eq!((profile.leet_mode, profile.leet_level), (LeetMode::Both, 3));
+        assert_eq!(PasswordMakerProfile::default().characters, PasswordMakerProfile::DEFAULT_CHARACTERS);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,172 @@
+//! age encryption of profile stores and exports.
+//! Site profiles are not secret, but they reveal which services someone uses.
+//! Files are encrypted with a passphrase or to an X25519 recipient, and the identity can be derived
+//! from the master password with `generator::derive_age_secret`, so no key file has to be kept.
+//!
+//! # Examples
+//! ```
+//! use kg_passgen::encryption::{decrypt, encrypt, AgeKey};
+//! let key = AgeKey::derived("my_master_password");
+//! let encrypted = encrypt(b"{\"sites\": []}", &key).unwrap();
+//! assert!(encrypted.starts_with(b"age-encryption.org/v1"));
+//! assert_eq!(decrypt(&encrypted, &key).unwrap(), b"{\"sites\": []}");
+//!
+//! // Anyone can encrypt to the public recipient, only the master password decrypts
+//! let recipient = AgeKey::Recipient(key.recipient().unwrap());
+//! let encrypted = encrypt(b"secret", &recipient).unwrap();
+//! assert_eq!(decrypt(&encrypted, &key).unwrap(), b"secret");
+//! ```
+
+use core::fmt;
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+use age::secrecy::SecretString;
+use bech32::ToBase32;
+
+const SECRET_KEY_PREFIX: &str = "age-secret-key-";
+
+/// Custom error type for files that could not be encrypted or decrypted
+#[derive(Debug, Clone)]
+pub struct AgeError(pub String);
+
+impl fmt::Display for AgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "age: {}", self.0)
+    }
+}
+
+/// Key encrypting or decrypting a file
+#[derive(PartialEq, Clone)]
+pub enum AgeKey {
+    /// A passphrase, stretched with scrypt by age
+    Passphrase(String),
+    /// An X25519 identity `AGE-SECRET-KEY-1...`, files are encrypted to its recipient
+    Identity(String),
+    /// An X25519 recipient `age1...`, which can only encrypt
+    Recipient(String),
+}
+
+impl fmt::Debug for AgeKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AgeKey::Passphrase(_) => write!(f, "Passphrase(..)"),
+            AgeKey::Identity(_) => write!(f, "Identity(..)"),
+            AgeKey::Recipient(recipient) => write!(f, "Recipient({})", recipient),
+        }
+    }
+}
+
+impl AgeKey {
+    /// Returns the X25519 identity derived from the master password
+    pub fn derived(master_password: &str) -> Self {
+        AgeKey::from_secret(&crate::generator::derive_age_secret(master_password, ""))
+    }
+
+    /// Returns the X25519 identity of a 32 byte secret
+    pub fn from_secret(secret: &[u8; 32]) -> Self {
+        let encoded = bech32::encode(SECRET_KEY_PREFIX, secret.to_base32(), bech32::Variant::Bech32).unwrap();
+        AgeKey::Identity(encoded.to_uppercase())
+    }
+
+    fn identity(identity: &str) -> Result<age::x25519::Identity, AgeError> {
+        age::x25519::Identity::from_str(identity.trim()).map_err(|e| AgeError(format!("invalid identity, {}", e)))
+    }
+
+    /// Returns the `age1...` recipient of an identity or recipient key
+    pub fn recipient(&self) -> Option<String> {
+        match self {
+            AgeKey::Passphrase(_) => None,
+            AgeKey::Identity(identity) => AgeKey::identity(identity).ok().map(|identity| identity.to_public().to_string()),
+            AgeKey::Recipient(recipient) => Some(recipient.trim().to_string()),
+        }
+    }
+}
+
+/// Encrypts the data into a binary age file
+pub fn encrypt(data: &[u8], key: &AgeKey) -> Result<Vec<u8>, AgeError> {
+    let encryptor = match key {
+        AgeKey::Passphrase(passphrase) => age::Encryptor::with_user_passphrase(SecretString::from(passphrase.clone())),
+        _ => {
+            let recipient = key.recipient().ok_or_else(|| AgeError("invalid identity".to_string()))?;
+            let recipient = age::x25519::Recipient::from_str(&recipient).map_err(|e| AgeError(format!("invalid recipient, {}", e)))?;
+            age::Encryptor::with_recipients(std::iter::once(&recipient as _)).map_err(|e| AgeError(e.to_string()))?
+        },
+    };
+
+    let mut output = Vec::new();
+    let mut writer = encryptor.wrap_output(&mut output).map_err(|e| AgeError(e.to_string()))?;
+    writer.write_all(data).map_err(|e| AgeError(e.to_string()))?;
+    writer.finish().map_err(|e| AgeError(e.to_string()))?;
+    Ok(output)
+}
+
+/// Decrypts an age file with a passphrase or identity, binary or ASCII armored
+pub fn decrypt(data: &[u8], key: &AgeKey) -> Result<Vec<u8>, AgeError> {
+    let decryptor = age::Decryptor::new_buffered(age::armor::ArmoredReader::new(data)).map_err(|e| AgeError(e.to_string()))?;
+    let mut reader = match key {
+        AgeKey::Passphrase(passphrase) => {
+            let identity = age::scrypt::Identity::new(SecretString::from(passphrase.clone()));
+            decryptor.decrypt(std::iter::once(&identity as _))
+        },
+        AgeKey::Identity(identity) => decryptor.decrypt(std::iter::once(&AgeKey::identity(identity)? as _)),
+        AgeKey::Recipient(_) => return Err(AgeError("a recipient cannot decrypt, an identity is required".to_string())),
+    }.map_err(|e| AgeError(e.to_string()))?;
+
+    let mut output = Vec::new();
+    reader.read_to_end(&mut output).map_err(|e| AgeError(e.to_string()))?;
+    Ok(output)
+}
+
+/// Checks whether the data is an age file
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(b"age-encryption.org/") || data.starts_with(b"-----BEGIN AGE ENCRYPTED FILE-----")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use age::secrecy::ExposeSecret;
+
+    #[test]
+    fn test_derived_identity_is_a_valid_age_identity() {
+        let AgeKey::Identity(identity) = AgeKey::derived("my_master_password") else { panic!() };
+        assert!(identity.starts_with("AGE-SECRET-KEY-1"));
+        let parsed = age::x25519::Identity::from_str(&identity).unwrap();
+        assert_eq!(parsed.to_string().expose_secret(), identity);
+        assert_ne!(AgeKey::derived("other"), AgeKey::derived("my_master_password"));
+    }
+
+    #[test]
+    fn test_wrong_keys_fail_to_decrypt() {
+        let encrypted = encrypt(b"data", &AgeKey::derived("my_master_password")).unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert!(decrypt(&encrypted, &AgeKey::derived("other")).is_err());
+        assert!(decrypt(&encrypted, &AgeKey::Recipient(AgeKey::derived("my_master_password").recipient().unwrap())).is_err());
+        assert!(decrypt(b"{}", &AgeKey::derived("my_master_password")).is_err());
+        assert!(encrypt(b"data", &AgeKey::Recipient("age1invalid".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_passphrase_round_trip() {
+        let encrypted = encrypt(b"data", &AgeKey::Passphrase("correct horse".to_string())).unwrap();
+        assert_eq!(decrypt(&encrypted, &AgeKey::Passphrase("correct horse".to_string())).unwrap(), b"data");
+        assert!(decrypt(&encrypted, &AgeKey::derived("correct horse")).is_err());
+    }
+
+    #[test]
+    fn test_armored_round_trip() {
+        let key = AgeKey::derived("my_master_password");
+        let recipient = age::x25519::Recipient::from_str(&key.recipient().unwrap()).unwrap();
+        let encryptor = age::Encryptor::with_recipients(std::iter::once(&recipient as _)).unwrap();
+        let mut armored = Vec::new();
+        let output = age::armor::ArmoredWriter::wrap_output(&mut armored, age::armor::Format::AsciiArmor).unwrap();
+        let mut writer = encryptor.wrap_output(output).unwrap();
+        writer.write_all(b"data").unwrap();
+        writer.finish().and_then(|output| output.finish()).unwrap();
+
+        assert!(armored.starts_with(b"-----BEGIN AGE ENCRYPTED FILE-----"));
+        assert!(is_encrypted(&armored));
+        assert_eq!(decrypt(&armored, &key).unwrap(), b"data");
+    }
+}
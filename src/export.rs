@@ -0,0 +1,173 @@
+//! Exports of derived passwords to other password managers, for disaster recovery or migration.
+//! KeePass XML and KDBX4, Bitwarden JSON, 1Password CSV and pass (password-store) trees are supported.
+//! Every site of a `ProfileStore` gets its password from `generate_password`,
+//! and the notes of each entry record the `Config` used so the password can be derived again.
+//!
+//! # Examples
+//! ```
+//! use kg_passgen::config::Config;
+//! use kg_passgen::export::{export_entries, keepass};
+//! use kg_passgen::profile::{ProfileStore, SiteProfile};
+//! let store = ProfileStore::default().with_site(SiteProfile::new("https://www.example.com").with_username("me"));
+//!
+//! let entries = export_entries(&store, "my_master_password", &Config::KGPG).unwrap();
+//! assert_eq!(entries[0].title, "example.com");
+//! assert_eq!(entries[0].password, "mXApUt1OgTb$xZh");
+//! assert!(keepass::to_xml(&entries).contains("<Value>me</Value>"));
+//! ```
+
+use sha2::{Digest, Sha256};
+
+use crate::config::{Config, GeneratorType};
+use crate::generator::{generate_password, GenerationError};
+use crate::profile::ProfileStore;
+
+pub mod bitwarden;
+pub mod keepass;
+pub mod onepassword;
+pub mod pass;
+
+/// A site with its derived password, ready to be written by an exporter
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ExportEntry {
+    /// The host used to generate the password
+    pub title: String,
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    pub group: String,
+    pub notes: String,
+    /// The `config_fingerprint` of the config used
+    pub fingerprint: String,
+}
+
+/// Version of the `config_encoding` format, bumped whenever the encoding changes
+pub const CONFIG_ENCODING_VERSION: u32 = 1;
+
+/// Encodes the settings of the config that affect its passwords, one `key=value` line each.
+/// Strings are quoted, so no value can run into the next line.
+/// `suffix_list_version` is a stamp of the store and is left out, the version of the list in use is encoded instead.
+pub fn config_encoding(config: &Config) -> String {
+    let mut fields = vec![("version", CONFIG_ENCODING_VERSION.to_string())];
+    match &config.generator_type {
+        GeneratorType::KGPG => fields.push(("generator", "kgpg".to_string())),
+        GeneratorType::SGP => fields.push(("generator", "sgp".to_string())),
+        GeneratorType::LessPass(profile) => fields.extend([
+            ("generator", "lesspass".to_string()),
+            ("login", format!("{:?}", profile.login)),
+            ("counter", profile.counter.to_string()),
+            ("character_sets", format!("{} {} {} {}", profile.lowercase, profile.uppercase, profile.digits, profile.symbols)),
+        ]),
+        GeneratorType::Spectre(profile) => fields.extend([
+            ("generator", "spectre".to_string()),
+            ("full_name", format!("{:?}", profile.full_name)),
+            ("counter", profile.counter.to_string()),
+            ("template", format!("{:?}", profile.template)),
+        ]),
+        GeneratorType::PasswordMaker(profile) => {
+            let components = &profile.url_components;
+            fields.extend([
+                ("generator", "passwordmaker".to_string()),
+                ("algorithm", format!("{:?}", profile.algorithm)),
+                ("hmac", profile.hmac.to_string()),
+                ("characters", format!("{:?}", profile.characters)),
+                ("url_components", format!("{} {} {} {}", components.protocol, components.subdomains, components.domain, components.port_path)),
+                ("username", format!("{:?}", profile.username)),
+                ("modifier", format!("{:?}", profile.modifier)),
+                ("prefix", format!("{:?}", profile.prefix)),
+                ("suffix", format!("{:?}", profile.suffix)),
+                ("leet", format!("{:?} {}", profile.leet_mode, profile.leet_level)),
+            ]);
+        },
+        GeneratorType::Pronounceable(profile) => fields.extend([
+            ("generator", "pronounceable".to_string()),
+            ("capitalize", profile.capitalize.to_string()),
+            ("digits", profile.digits.to_string()),
+            ("symbol", profile.symbol.to_string()),
+        ]),
+    }
+    fields.push(("hash", format!("{:?}", config.hash_algorithm)));
+    match &config.template {
+        Some(template) => fields.push(("template", format!("{:?}", template.to_string()))),
+        None => fields.push(("length", config.length.to_string())),
+    }
+    fields.extend([
+        ("hops", config.hops.to_string()),
+        ("strip_subdomain", config.strip_subdomain.to_string()),
+        ("include_port", config.include_port.to_string()),
+        ("equivalences", format!("{:?}", config.equivalences.groups)),
+        ("apps", format!("{:?}", config.apps.mappings)),
+        ("suffix_list", format!("{:?}", config.suffix_list.version())),
+    ]);
+    fields.iter().map(|(key, value)| format!("{}={}\n", key, value)).collect()
+}
+
+/// Returns a short hex fingerprint of the `config_encoding` of the config,
+/// two exports share it only when their passwords come from the same settings
+/// # Examples
+/// ```
+/// use kg_passgen::config::Config;
+/// use kg_passgen::export::config_fingerprint;
+/// assert_eq!(config_fingerprint(&Config::KGPG).len(), 16);
+/// assert_ne!(config_fingerprint(&Config::KGPG), config_fingerprint(&Config::SGP));
+/// assert_eq!(config_fingerprint(&Config::KGPG), config_fingerprint(&Config::KGPG.with_suffix_list_version("psl-2.1.175")));
+/// ```
+pub fn config_fingerprint(config: &Config) -> String {
+    Sha256::digest(config_encoding(config).as_bytes())[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Describes the config in the notes of exported entries
+pub fn config_notes(config: &Config) -> String {
+    let length = match &config.template {
+        Some(template) => format!("template {}", template),
+        None => format!("length {}", config.length),
+    };
+    format!(
+        "Generated by kg_passgen with {:?}, {:?}, {}, hops {}, strip subdomain {}, include port {}, public suffix list {} (config {})",
+        config.generator_type, config.hash_algorithm, length, config.hops, config.strip_subdomain, config.include_port,
+        config.suffix_list.version(), config_fingerprint(config),
+    )
+}
+
+/// Derives the password of every site of the store.
+/// The title of an entry is the host the password was generated for.
+pub fn export_entries(store: &ProfileStore, master_password: &str, config: &Config) -> Result<Vec<ExportEntry>, GenerationError> {
+    let config_notes = config_notes(config);
+    let fingerprint = config_fingerprint(config);
+    store.sites.iter().map(|site| {
+        let notes = match site.notes.trim() {
+            "" => config_notes.clone(),
+            notes => format!("{}\n{}", notes, config_notes),
+        };
+        Ok(ExportEntry {
+            title: crate::url_helper::get_config_host(&site.url, config),
+            url: site.url.clone(),
+            username: site.username.clone(),
+            password: generate_password(&site.url, master_password, config)?,
+            group: site.group.clone(),
+            notes,
+            fingerprint: fingerprint.clone(),
+        })
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_fingerprint_is_pinned() {
+        assert_eq!(config_encoding(&Config::KGPG), "version=1\ngenerator=kgpg\nhash=SHA512\nlength=15\nhops=15\nstrip_subdomain=true\ninclude_port=false\nequivalences=[]\napps=[]\nsuffix_list=\"psl-2.1.175\"\n");
+        assert_eq!(config_fingerprint(&Config::KGPG), "e82bc9e09282dd04");
+    }
+
+    #[test]
+    fn test_config_fingerprint_covers_password_settings() {
+        let fingerprint = config_fingerprint(&Config::KGPG);
+        assert_ne!(fingerprint, config_fingerprint(&Config::KGPG.with_include_port(true)));
+        assert_ne!(fingerprint, config_fingerprint(&Config::KGPG.with_template("a{15}".parse().unwrap())));
+        assert_ne!(fingerprint, config_fingerprint(&Config::KGPG.with_equivalences(crate::url_helper::EquivalenceTable::builtin())));
+        let lesspass = config_fingerprint(&Config::LESSPASS);
+        assert_ne!(lesspass, config_fingerprint(&Config::LESSPASS.with_generator_type(GeneratorType::LessPass(crate::config::LessPassProfile::default().with_counter(2)))));
+    }
+}
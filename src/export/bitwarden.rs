@@ -0,0 +1,152 @@
+//! Bitwarden exports in its unencrypted JSON import format.
+//! Groups become folders, and ids are derived from the site so a later export produces the same ids.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::export::ExportEntry;
+
+/// Item type of logins
+pub const LOGIN_TYPE: u8 = 1;
+
+/// A Bitwarden JSON export
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BitwardenExport {
+    #[serde(default)]
+    pub encrypted: bool,
+    #[serde(default)]
+    pub folders: Vec<BitwardenFolder>,
+    #[serde(default)]
+    pub items: Vec<BitwardenItem>,
+}
+
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct BitwardenFolder {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BitwardenItem {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub folder_id: Option<String>,
+    #[serde(rename = "type")]
+    pub item_type: u8,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub favorite: bool,
+    #[serde(default)]
+    pub login: Option<BitwardenLogin>,
+}
+
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct BitwardenLogin {
+    #[serde(default)]
+    pub uris: Vec<BitwardenUri>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub totp: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct BitwardenUri {
+    #[serde(rename = "match")]
+    pub match_type: Option<u8>,
+    pub uri: Option<String>,
+}
+
+/// Returns a UUID formatted id derived from the seed
+fn id(seed: &str) -> String {
+    let hex: String = Sha256::digest(format!("kg_passgen:bitwarden:{}", seed).as_bytes())[..16].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+fn optional(text: &str) -> Option<String> {
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+impl BitwardenExport {
+    /// Builds an export holding one login per entry
+    pub fn from_entries(entries: &[ExportEntry]) -> Self {
+        let mut export = BitwardenExport::default();
+        for entry in entries {
+            let folder_id = optional(&entry.group).map(|group| id(&format!("folder:{}", group)));
+            if let Some(folder_id) = &folder_id && !export.folders.iter().any(|folder| &folder.id == folder_id) {
+                export.folders.push(BitwardenFolder { id: folder_id.clone(), name: entry.group.clone() });
+            }
+
+            let seed = format!("item:{}:{}:{}", entry.group, entry.url, entry.username);
+            let item_id = (0..).map(|i| if i == 0 { id(&seed) } else { id(&format!("{}:{}", seed, i)) })
+                .find(|item_id| !export.items.iter().any(|item| &item.id == item_id)).unwrap();
+            export.items.push(BitwardenItem {
+                id: item_id,
+                folder_id,
+                item_type: LOGIN_TYPE,
+                name: entry.title.clone(),
+                notes: optional(&entry.notes),
+                favorite: false,
+                login: Some(BitwardenLogin {
+                    uris: vec![BitwardenUri { match_type: None, uri: Some(entry.url.clone()) }],
+                    username: optional(&entry.username),
+                    password: Some(entry.password.clone()),
+                    totp: None,
+                }),
+            });
+        }
+        export
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+/// Writes the entries in Bitwarden's JSON import format
+/// # Examples
+/// ```
+/// use kg_passgen::export::ExportEntry;
+/// use kg_passgen::export::bitwarden::to_json;
+/// let entry = ExportEntry { title: "example.com".to_string(), url: "https://example.com".to_string(), password: "secret".to_string(), ..Default::default() };
+/// let json = to_json(&[entry]);
+/// assert!(json.contains("\"password\": \"secret\""));
+/// assert!(json.contains("\"encrypted\": false"));
+/// ```
+pub fn to_json(entries: &[ExportEntry]) -> String {
+    BitwardenExport::from_entries(entries).to_json()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_entries_creates_folders_and_unique_ids() {
+        let entry = ExportEntry { title: "mail.com".to_string(), url: "mail.com".to_string(), password: "secret".to_string(), group: "Mail".to_string(), ..Default::default() };
+        let export = BitwardenExport::from_entries(&[entry.clone(), entry, ExportEntry::default()]);
+
+        assert_eq!(export.folders.len(), 1);
+        assert_eq!(export.items[0].folder_id.as_ref(), Some(&export.folders[0].id));
+        assert_eq!(export.items[2].folder_id, None);
+        assert_ne!(export.items[0].id, export.items[1].id);
+        assert_eq!(export.items[0].id.len(), 36);
+
+        let json = export.to_json();
+        assert!(json.contains("\"folderId\""));
+        assert!(json.contains("\"type\": 1"));
+        assert_eq!(BitwardenExport::from_json(&json).unwrap(), export);
+    }
+}
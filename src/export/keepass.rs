@@ -0,0 +1,376 @@
+//! KeePass 2.x exports: plain XML, as imported by KeePass and KeePassXC, or a KDBX4 database.
+//! Entries of a group go in a subgroup of the `kg_passgen` root group.
+//! UUIDs are derived from the site, so a later export synchronizes into the same entries.
+//!
+//! KDBX4 databases are encrypted with ChaCha20, and the key is derived from the password with Argon2id.
+//! Passwords are encrypted with the ChaCha20 inner stream of the database and marked `Protected`, as KeePass and KeePassXC expect.
+
+use core::fmt;
+use std::collections::HashSet;
+
+use base64::Engine;
+use chacha20::ChaCha20;
+use chacha20::cipher::{KeyIvInit, StreamCipher};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::export::ExportEntry;
+
+const ROOT_GROUP: &str = "kg_passgen";
+
+const SIGNATURE_1: u32 = 0x9AA2_D903;
+const SIGNATURE_2: u32 = 0xB54B_FB67;
+const VERSION_4: u32 = 0x0004_0000;
+const CHACHA20_UUID: [u8; 16] = [0xD6, 0x03, 0x8A, 0x2B, 0x8B, 0x6F, 0x4C, 0xB5, 0xA5, 0x24, 0x33, 0x9A, 0x31, 0xDB, 0xB5, 0x9A];
+const ARGON2ID_UUID: [u8; 16] = [0x9E, 0x29, 0x8B, 0x19, 0x56, 0xDB, 0x47, 0x73, 0xB2, 0x3D, 0xFC, 0x3E, 0xC6, 0xF0, 0xA1, 0xE6];
+const BLOCK_SIZE: usize = 1024 * 1024;
+
+/// Custom error type for KDBX databases that could not be written
+#[derive(Debug, Clone)]
+pub struct KdbxError(pub String);
+
+impl fmt::Display for KdbxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to write the KDBX database: {}", self.0)
+    }
+}
+
+/// Argon2id settings of KDBX4 databases
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Kdbx4Options {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for Kdbx4Options {
+    fn default() -> Self {
+        Kdbx4Options::DEFAULT
+    }
+}
+
+impl Kdbx4Options {
+    pub const DEFAULT: Kdbx4Options = Kdbx4Options { memory_kib: 64 * 1024, iterations: 10, parallelism: 2 };
+
+    pub fn with_memory_kib(mut self, memory_kib: u32) -> Self { self.memory_kib = memory_kib ; self }
+    pub fn with_iterations(mut self, iterations: u32) -> Self { self.iterations = iterations ; self }
+    pub fn with_parallelism(mut self, parallelism: u32) -> Self { self.parallelism = parallelism ; self }
+}
+
+/// Escapes text for XML, characters XML cannot hold are dropped
+fn escape_xml(text: &str) -> String {
+    text.chars().filter(|c| matches!(c, '\t' | '\n' | '\r') || *c >= ' ').map(|c| match c {
+        '&' => "&amp;".to_string(),
+        '<' => "&lt;".to_string(),
+        '>' => "&gt;".to_string(),
+        '"' => "&quot;".to_string(),
+        '\'' => "&apos;".to_string(),
+        c => c.to_string(),
+    }).collect()
+}
+
+fn uuid(seed: &str) -> String {
+    base64::prelude::BASE64_STANDARD.encode(&Sha256::digest(format!("kg_passgen:keepass:{}", seed).as_bytes())[..16])
+}
+
+/// How password values are written
+enum Protection {
+    /// Plain XML files mark them `ProtectInMemory`
+    InMemory,
+    /// KDBX4 databases encrypt them with the inner stream, each value continuing the keystream in document order
+    Stream(ChaCha20),
+}
+
+impl Protection {
+    /// Returns the inner stream of a KDBX4 database, keyed with the SHA512 of its stream key
+    fn stream(stream_key: &[u8]) -> Self {
+        let digest = Sha512::digest(stream_key);
+        Protection::Stream(ChaCha20::new(digest[..32].into(), digest[32..44].into()))
+    }
+
+    fn value(&mut self, text: &str) -> String {
+        match self {
+            Protection::InMemory => format!("<Value ProtectInMemory=\"True\">{}</Value>", escape_xml(text)),
+            Protection::Stream(cipher) => {
+                let mut bytes = text.as_bytes().to_vec();
+                cipher.apply_keystream(&mut bytes);
+                format!("<Value Protected=\"True\">{}</Value>", base64::prelude::BASE64_STANDARD.encode(bytes))
+            },
+        }
+    }
+}
+
+fn write_entry(xml: &mut String, entry: &ExportEntry, uuids: &mut HashSet<String>, indent: &str, protection: &mut Protection) {
+    let seed = format!("entry:{}:{}:{}", entry.group, entry.url, entry.username);
+    let uuid = (0..).map(|i| if i == 0 { uuid(&seed) } else { uuid(&format!("{}:{}", seed, i)) }).find(|uuid| uuids.insert(uuid.clone())).unwrap();
+
+    xml.push_str(&format!("{}<Entry>\n{}\t<UUID>{}</UUID>\n", indent, indent, uuid));
+    for (key, value) in [("Title", &entry.title), ("UserName", &entry.username), ("Password", &entry.password), ("URL", &entry.url), ("Notes", &entry.notes)] {
+        let value = match key {
+            "Password" => protection.value(value),
+            _ => format!("<Value>{}</Value>", escape_xml(value)),
+        };
+        xml.push_str(&format!("{}\t<String>\n{}\t\t<Key>{}</Key>\n{}\t\t{}\n{}\t</String>\n", indent, indent, key, indent, value, indent));
+    }
+    xml.push_str(&format!("{}</Entry>\n", indent));
+}
+
+/// Writes the entries as a KeePass 2.x XML file
+/// # Examples
+/// ```
+/// use kg_passgen::export::ExportEntry;
+/// use kg_passgen::export::keepass::to_xml;
+/// let entry = ExportEntry { title: "example.com".to_string(), password: "a<b".to_string(), group: "Work".to_string(), ..Default::default() };
+/// let xml = to_xml(&[entry]);
+/// assert!(xml.contains("<Name>Work</Name>"));
+/// assert!(xml.contains("<Value ProtectInMemory=\"True\">a&lt;b</Value>"));
+/// ```
+pub fn to_xml(entries: &[ExportEntry]) -> String {
+    write_xml(entries, &mut Protection::InMemory)
+}
+
+fn write_xml(entries: &[ExportEntry], protection: &mut Protection) -> String {
+    let mut uuids = HashSet::new();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\" standalone=\"yes\"?>\n<KeePassFile>\n");
+    xml.push_str(&format!("\t<Meta>\n\t\t<Generator>kg_passgen</Generator>\n\t\t<DatabaseName>{}</DatabaseName>\n\t</Meta>\n", ROOT_GROUP));
+    xml.push_str(&format!("\t<Root>\n\t\t<Group>\n\t\t\t<UUID>{}</UUID>\n\t\t\t<Name>{}</Name>\n", uuid("group:"), ROOT_GROUP));
+
+    for entry in entries.iter().filter(|entry| entry.group.is_empty()) {
+        write_entry(&mut xml, entry, &mut uuids, "\t\t\t", protection);
+    }
+
+    let mut groups: Vec<&str> = Vec::new();
+    for entry in entries.iter().filter(|entry| !entry.group.is_empty()) {
+        if !groups.contains(&entry.group.as_str()) {
+            groups.push(&entry.group);
+        }
+    }
+    for group in groups {
+        xml.push_str(&format!("\t\t\t<Group>\n\t\t\t\t<UUID>{}</UUID>\n\t\t\t\t<Name>{}</Name>\n", uuid(&format!("group:{}", group)), escape_xml(group)));
+        for entry in entries.iter().filter(|entry| entry.group == group) {
+            write_entry(&mut xml, entry, &mut uuids, "\t\t\t\t", protection);
+        }
+        xml.push_str("\t\t\t</Group>\n");
+    }
+
+    xml.push_str("\t\t</Group>\n\t</Root>\n</KeePassFile>\n");
+    xml
+}
+
+fn random_bytes<const N: usize>() -> Result<[u8; N], KdbxError> {
+    let mut bytes = [0u8; N];
+    getrandom::fill(&mut bytes).map_err(|e| KdbxError(e.to_string()))?;
+    Ok(bytes)
+}
+
+fn push_field(buffer: &mut Vec<u8>, id: u8, data: &[u8]) {
+    buffer.push(id);
+    buffer.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(data);
+}
+
+fn push_variant(buffer: &mut Vec<u8>, kind: u8, key: &str, value: &[u8]) {
+    buffer.push(kind);
+    buffer.extend_from_slice(&(key.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(key.as_bytes());
+    buffer.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(value);
+}
+
+/// Returns the HMAC-SHA256 key of a block, the header uses the block index `u64::MAX`
+fn block_key(hmac_base: &[u8], index: u64) -> [u8; 64] {
+    Sha512::new().chain_update(index.to_le_bytes()).chain_update(hmac_base).finalize().into()
+}
+
+fn block_hmac(hmac_base: &[u8], index: u64, parts: &[&[u8]]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(&block_key(hmac_base, index)).unwrap();
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+/// Returns the cipher key and the HMAC base key of a database
+fn database_keys(password: &str, master_seed: &[u8], salt: &[u8], options: &Kdbx4Options) -> Result<([u8; 32], [u8; 64]), KdbxError> {
+    let composite = Sha256::digest(Sha256::digest(password.as_bytes()));
+    let params = argon2::Params::new(options.memory_kib, options.iterations, options.parallelism, Some(32)).map_err(|e| KdbxError(e.to_string()))?;
+    let mut transformed = [0u8; 32];
+    argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+        .hash_password_into(&composite, salt, &mut transformed)
+        .map_err(|e| KdbxError(e.to_string()))?;
+
+    let cipher_key = Sha256::new().chain_update(master_seed).chain_update(transformed).finalize().into();
+    let hmac_base = Sha512::new().chain_update(master_seed).chain_update(transformed).chain_update([1]).finalize().into();
+    Ok((cipher_key, hmac_base))
+}
+
+/// Writes the entries as a KDBX4 database protected by the password
+/// # Examples
+/// ```
+/// use kg_passgen::export::ExportEntry;
+/// use kg_passgen::export::keepass::{to_kdbx4, Kdbx4Options};
+/// let options = Kdbx4Options::DEFAULT.with_memory_kib(64).with_iterations(1);
+/// let database = to_kdbx4(&[ExportEntry::default()], "database password", &options).unwrap();
+/// assert_eq!(database[..4], [0x03, 0xD9, 0xA2, 0x9A]);
+/// ```
+pub fn to_kdbx4(entries: &[ExportEntry], password: &str, options: &Kdbx4Options) -> Result<Vec<u8>, KdbxError> {
+    let master_seed = random_bytes::<32>()?;
+    let iv = random_bytes::<12>()?;
+    let salt = random_bytes::<32>()?;
+    let stream_key = random_bytes::<64>()?;
+    let (cipher_key, hmac_base) = database_keys(password, &master_seed, &salt, options)?;
+
+    let mut kdf = 0x0100u16.to_le_bytes().to_vec();
+    push_variant(&mut kdf, 0x42, "$UUID", &ARGON2ID_UUID);
+    push_variant(&mut kdf, 0x42, "S", &salt);
+    push_variant(&mut kdf, 0x04, "P", &options.parallelism.to_le_bytes());
+    push_variant(&mut kdf, 0x05, "M", &(options.memory_kib as u64 * 1024).to_le_bytes());
+    push_variant(&mut kdf, 0x05, "I", &(options.iterations as u64).to_le_bytes());
+    push_variant(&mut kdf, 0x04, "V", &0x13u32.to_le_bytes());
+    kdf.push(0);
+
+    let mut header = [SIGNATURE_1.to_le_bytes(), SIGNATURE_2.to_le_bytes(), VERSION_4.to_le_bytes()].concat();
+    push_field(&mut header, 2, &CHACHA20_UUID);
+    push_field(&mut header, 3, &0u32.to_le_bytes());
+    push_field(&mut header, 4, &master_seed);
+    push_field(&mut header, 7, &iv);
+    push_field(&mut header, 11, &kdf);
+    push_field(&mut header, 0, b"\r\n\r\n");
+
+    let mut payload = Vec::new();
+    push_field(&mut payload, 1, &3u32.to_le_bytes());
+    push_field(&mut payload, 2, &stream_key);
+    push_field(&mut payload, 0, &[]);
+    payload.extend_from_slice(write_xml(entries, &mut Protection::stream(&stream_key)).as_bytes());
+    ChaCha20::new(&cipher_key.into(), &iv.into()).apply_keystream(&mut payload);
+
+    let mut database = header.clone();
+    database.extend_from_slice(&Sha256::digest(&header));
+    database.extend_from_slice(&block_hmac(&hmac_base, u64::MAX, &[&header]));
+    for (index, block) in payload.chunks(BLOCK_SIZE).chain([&[][..]]).enumerate() {
+        let size = (block.len() as u32).to_le_bytes();
+        database.extend_from_slice(&block_hmac(&hmac_base, index as u64, &[&(index as u64).to_le_bytes(), &size, block]));
+        database.extend_from_slice(&size);
+        database.extend_from_slice(block);
+    }
+    Ok(database)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPTIONS: Kdbx4Options = Kdbx4Options { memory_kib: 64, iterations: 1, parallelism: 1 };
+
+    fn read_u32(bytes: &[u8], position: usize) -> u32 {
+        u32::from_le_bytes(bytes[position..position + 4].try_into().unwrap())
+    }
+
+    /// Reads back a database written by `to_kdbx4`, checking every hash and HMAC
+    fn decrypt(database: &[u8], password: &str) -> Option<String> {
+        let mut fields = std::collections::HashMap::new();
+        let mut position = 12;
+        loop {
+            let (id, size) = (database[position], read_u32(database, position + 1) as usize);
+            fields.insert(id, &database[position + 5..position + 5 + size]);
+            position += 5 + size;
+            if id == 0 {
+                break;
+            }
+        }
+        let header = &database[..position];
+        assert_eq!(&database[position..position + 32], Sha256::digest(header).as_slice());
+
+        let kdf = fields[&11];
+        let salt_position = kdf.windows(6).position(|window| window == b"\x42\x01\0\0\0S").unwrap() + 10;
+        let salt = &kdf[salt_position..salt_position + 32];
+        let (cipher_key, hmac_base) = database_keys(password, fields[&4], salt, &OPTIONS).unwrap();
+        if database[position + 32..position + 64] != block_hmac(&hmac_base, u64::MAX, &[header]) {
+            return None;
+        }
+
+        let mut payload = Vec::new();
+        position += 64;
+        for index in 0u64.. {
+            let size = read_u32(database, position + 32) as usize;
+            let block = &database[position + 36..position + 36 + size];
+            assert_eq!(database[position..position + 32], block_hmac(&hmac_base, index, &[&index.to_le_bytes(), &(size as u32).to_le_bytes(), block]));
+            position += 36 + size;
+            if size == 0 {
+                break;
+            }
+            payload.extend_from_slice(block);
+        }
+        assert_eq!(position, database.len());
+
+        let iv: [u8; 12] = fields[&7].try_into().unwrap();
+        ChaCha20::new(&cipher_key.into(), &iv.into()).apply_keystream(&mut payload);
+        assert_eq!(payload[..9], [1, 4, 0, 0, 0, 3, 0, 0, 0]);
+        assert_eq!(payload[9..14], [2, 64, 0, 0, 0]);
+        assert_eq!(payload[78..83], [0, 0, 0, 0, 0]);
+        let xml = String::from_utf8(payload[83..].to_vec()).ok()?;
+
+        // Protected values continue the inner stream in document order
+        let Protection::Stream(mut cipher) = Protection::stream(&payload[14..78]) else { unreachable!() };
+        let mut unprotected = xml.clone();
+        for value in xml.split("<Value Protected=\"True\">").skip(1).map(|rest| rest.split_once("</Value>").unwrap().0) {
+            let mut bytes = base64::prelude::BASE64_STANDARD.decode(value).unwrap();
+            cipher.apply_keystream(&mut bytes);
+            let plain = format!("<Value ProtectInMemory=\"True\">{}</Value>", escape_xml(&String::from_utf8(bytes).unwrap()));
+            unprotected = unprotected.replacen(&format!("<Value Protected=\"True\">{}</Value>", value), &plain, 1);
+        }
+        Some(unprotected)
+    }
+
+    fn entries() -> Vec<ExportEntry> {
+        vec![
+            ExportEntry { title: "example.com".to_string(), url: "https://example.com".to_string(), username: "me".to_string(), password: "p@ss<word>".to_string(), ..Default::default() },
+            ExportEntry { title: "mail.com".to_string(), url: "mail.com".to_string(), password: "secret".to_string(), group: "Mail & Chat".to_string(), ..Default::default() },
+            ExportEntry { title: "mail.com".to_string(), url: "mail.com".to_string(), password: "secret".to_string(), group: "Mail & Chat".to_string(), ..Default::default() },
+        ]
+    }
+
+    #[test]
+    fn test_to_xml_groups_entries_with_unique_uuids() {
+        let xml = to_xml(&entries());
+        assert!(xml.contains("<Name>Mail &amp; Chat</Name>"));
+        assert!(xml.contains("<Value ProtectInMemory=\"True\">p@ss&lt;word&gt;</Value>"));
+        assert_eq!(xml.matches("<Group>").count(), 2);
+        assert_eq!(xml.matches("<Entry>").count(), 3);
+
+        let uuids: HashSet<&str> = xml.lines().filter_map(|line| line.trim().strip_prefix("<UUID>")).collect();
+        assert_eq!(uuids.len(), 5);
+        assert_eq!(xml, to_xml(&entries()));
+        assert_eq!(escape_xml("a\u{1}b\tc\"'"), "ab\tc&quot;&apos;");
+    }
+
+    #[test]
+    fn test_to_kdbx4_decrypts_to_the_xml() {
+        let database = to_kdbx4(&entries(), "database password", &OPTIONS).unwrap();
+        assert_eq!(read_u32(&database, 0), SIGNATURE_1);
+        assert_eq!(read_u32(&database, 4), SIGNATURE_2);
+        assert_eq!(read_u32(&database, 8), VERSION_4);
+        assert_eq!(decrypt(&database, "database password"), Some(to_xml(&entries())));
+        assert_eq!(decrypt(&database, "wrong password"), None);
+        assert_ne!(database, to_kdbx4(&entries(), "database password", &OPTIONS).unwrap());
+    }
+
+    #[test]
+    fn test_to_kdbx4_opens_with_the_keepass_crate() {
+        let database = to_kdbx4(&entries(), "database password", &OPTIONS).unwrap();
+        let key = keepass::DatabaseKey::new().with_password("database password");
+        let opened = keepass::Database::open(&mut database.as_slice(), key).unwrap();
+
+        assert_eq!(opened.root.name, ROOT_GROUP);
+        let entry = opened.root.entry_by_name("example.com").unwrap();
+        assert_eq!(entry.get_username(), Some("me"));
+        assert_eq!(entry.get_password(), Some("p@ss<word>"));
+        assert!(matches!(entry.fields["Password"], keepass::db::Value::Protected(_)));
+
+        let group = opened.root.group_by_name("Mail & Chat").unwrap();
+        assert_eq!(group.entries.len(), 2);
+        assert!(group.entries.iter().all(|entry| entry.get_password() == Some("secret")));
+        let key = keepass::DatabaseKey::new().with_password("wrong password");
+        assert!(keepass::Database::open(&mut database.as_slice(), key).is_err());
+    }
+}
@@ -0,0 +1,45 @@
+//! 1Password exports in its CSV import layout.
+//! Groups become tags, as 1Password imports every item into one vault.
+
+use crate::export::ExportEntry;
+
+/// Header row of the CSV file
+pub const HEADER: [&str; 6] = ["Title", "Website", "Username", "Password", "Notes", "Tags"];
+
+/// Quotes a field when it holds a comma, quote or line break
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Writes the entries in 1Password's CSV layout, one row per entry after the header
+/// # Examples
+/// ```
+/// use kg_passgen::export::ExportEntry;
+/// use kg_passgen::export::onepassword::to_csv;
+/// let entry = ExportEntry { title: "example.com".to_string(), url: "https://example.com".to_string(), password: "a,b".to_string(), ..Default::default() };
+/// assert_eq!(to_csv(&[entry]), "Title,Website,Username,Password,Notes,Tags\r\nexample.com,https://example.com,,\"a,b\",,\r\n");
+/// ```
+pub fn to_csv(entries: &[ExportEntry]) -> String {
+    let rows = entries.iter().map(|entry| {
+        [&entry.title, &entry.url, &entry.username, &entry.password, &entry.notes, &entry.group].map(|field| csv_field(field)).join(",")
+    });
+    std::iter::once(HEADER.join(",")).chain(rows).map(|row| row + "\r\n").collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_csv_quotes_fields() {
+        let entry = ExportEntry { title: "mail.com".to_string(), password: "say \"hi\"".to_string(), notes: "line\nbreak".to_string(), group: "Mail".to_string(), ..Default::default() };
+        let csv = to_csv(&[entry]);
+        assert_eq!(csv.lines().nth(1), Some("mail.com,,,\"say \"\"hi\"\"\",\"line"));
+        assert!(csv.ends_with("break\",Mail\r\n"));
+        assert_eq!(to_csv(&[]), "Title,Website,Username,Password,Notes,Tags\r\n");
+    }
+}
@@ -0,0 +1,199 @@
+//! pass (password-store) exports: one encrypted file per host, in a subdirectory per group.
+//! Files are encrypted by an external command reading the plaintext on stdin and writing the ciphertext to stdout,
+//! GPG as pass does, age, or any other command.
+//! The first line of a file is the password, followed by `username:`, `url:` and `kg_passgen-config:` lines.
+
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::export::ExportEntry;
+
+/// The command encrypting every file of the store
+#[derive(Debug, PartialEq, Clone)]
+pub struct PassEncryptor {
+    pub program: String,
+    pub args: Vec<String>,
+    /// Extension of the encrypted files, without the dot
+    pub extension: String,
+}
+
+impl PassEncryptor {
+    pub fn new(program: &str, args: &[&str], extension: &str) -> Self {
+        PassEncryptor { program: program.to_string(), args: args.iter().map(|arg| arg.to_string()).collect(), extension: extension.to_string() }
+    }
+
+    /// Encrypts with GPG for the recipients, with the options pass uses
+    pub fn gpg(recipients: &[&str]) -> Self {
+        let mut encryptor = PassEncryptor::new("gpg", &["--encrypt", "--quiet", "--yes", "--batch", "--compress-algo=none", "--no-encrypt-to"], "gpg");
+        for recipient in recipients {
+            encryptor.args.extend(["--recipient".to_string(), recipient.to_string()]);
+        }
+        encryptor
+    }
+
+    /// Encrypts with age for the recipients, as passage does
+    pub fn age(recipients: &[&str]) -> Self {
+        let mut encryptor = PassEncryptor::new("age", &["--encrypt"], "age");
+        for recipient in recipients {
+            encryptor.args.extend(["--recipient".to_string(), recipient.to_string()]);
+        }
+        encryptor
+    }
+
+    /// Parses a command line with `split_command`, `None` when it is empty or has an unclosed quote
+    pub fn from_command(command: &str, extension: &str) -> Option<Self> {
+        let mut words = split_command(command)?;
+        if words.is_empty() {
+            return None;
+        }
+        let program = words.remove(0);
+        Some(PassEncryptor { program, args: words, extension: extension.to_string() })
+    }
+
+    /// Reads the GPG recipients of a store from its `.gpg-id` file
+    pub fn gpg_for_store(directory: impl AsRef<Path>) -> io::Result<Self> {
+        let ids = std::fs::read_to_string(directory.as_ref().join(".gpg-id"))?;
+        Ok(PassEncryptor::gpg(&ids.lines().map(str::trim).filter(|id| !id.is_empty()).collect::<Vec<_>>()))
+    }
+
+    /// Runs the command on the plaintext and returns its output
+    pub fn encrypt(&self, plaintext: &[u8]) -> io::Result<Vec<u8>> {
+        let mut child = Command::new(&self.program).args(&self.args).stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::inherit()).spawn()?;
+        let mut stdin = child.stdin.take().unwrap();
+        let plaintext = plaintext.to_vec();
+        let writer = std::thread::spawn(move || stdin.write_all(&plaintext));
+
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!("{} exited with {}", self.program, output.status)));
+        }
+        writer.join().map_err(|_| io::Error::other("Failed to write to the encryption command"))??;
+        Ok(output.stdout)
+    }
+}
+
+/// Returns the contents of the file of an entry
+pub fn entry_contents(entry: &ExportEntry) -> String {
+    let mut contents = format!("{}\n", entry.password);
+    for (key, value) in [("username", &entry.username), ("url", &entry.url), ("kg_passgen-config", &entry.fingerprint)] {
+        if !value.is_empty() {
+            contents.push_str(&format!("{}: {}\n", key, value.replace(['\n', '\r'], " ")));
+        }
+    }
+    contents
+}
+
+/// Makes a name usable as a single path component
+fn path_component(name: &str) -> String {
+    match name.trim().replace(['/', '\\', '\0'], "-").as_str() {
+        "" | "." | ".." => "_".to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// Writes one encrypted file per host into the store directory and returns the written paths.
+/// Later entries for a host already written are skipped, existing files are overwritten.
+pub fn write_store(directory: impl AsRef<Path>, entries: &[ExportEntry], encryptor: &PassEncryptor) -> io::Result<Vec<PathBuf>> {
+    let mut hosts = HashSet::new();
+    let mut paths = Vec::new();
+    for entry in entries.iter().filter(|entry| hosts.insert(entry.title.clone())) {
+        let mut path = directory.as_ref().to_path_buf();
+        if !entry.group.trim().is_empty() {
+            path.push(path_component(&entry.group));
+        }
+        std::fs::create_dir_all(&path)?;
+        path.push(format!("{}.{}", path_component(&entry.title), encryptor.extension));
+
+        std::fs::write(&path, encryptor.encrypt(entry_contents(entry).as_bytes())?)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// Splits a command line into words like a shell does, without expansions.
+/// Single quotes keep everything as is, double quotes and backslashes escape spaces and quotes.
+/// Returns `None` on an unclosed quote or a trailing backslash.
+/// # Examples
+/// ```
+/// use kg_passgen::export::pass::split_command;
+/// assert_eq!(split_command("gpg --recipient \"John Doe\"").unwrap(), ["gpg", "--recipient", "John Doe"]);
+/// assert_eq!(split_command("age -r 'a b' c\\ d").unwrap(), ["age", "-r", "a b", "c d"]);
+/// assert!(split_command("gpg --recipient \"John").is_none());
+/// ```
+pub fn split_command(command: &str) -> Option<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                let (quoted, _) = chars.as_str().split_once('\'')?;
+                word.get_or_insert_default().push_str(quoted);
+                chars.nth(quoted.chars().count());
+            },
+            '"' => {
+                let word = word.get_or_insert_default();
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => match chars.next()? {
+                            escaped @ ('"' | '\\' | '$' | '`') => word.push(escaped),
+                            other => word.extend(['\\', other]),
+                        },
+                        other => word.push(other),
+                    }
+                }
+            },
+            '\\' => word.get_or_insert_default().push(chars.next()?),
+            c if c.is_whitespace() => words.extend(word.take()),
+            c => word.get_or_insert_default().push(c),
+        }
+    }
+    words.extend(word);
+    Some(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(title: &str, group: &str) -> ExportEntry {
+        ExportEntry { title: title.to_string(), url: format!("https://{}", title), username: "me".to_string(), password: "secret".to_string(), group: group.to_string(), fingerprint: "0123456789abcdef".to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_entry_contents_has_metadata_lines() {
+        assert_eq!(entry_contents(&entry("example.com", "")), "secret\nusername: me\nurl: https://example.com\nkg_passgen-config: 0123456789abcdef\n");
+        assert_eq!(entry_contents(&ExportEntry { password: "secret".to_string(), ..Default::default() }), "secret\n");
+        assert_eq!(path_component("../a/b"), "..-a-b");
+        assert_eq!(path_component(".."), "_");
+    }
+
+    #[test]
+    fn test_from_command_keeps_quoted_arguments() {
+        let encryptor = PassEncryptor::from_command("gpg --encrypt --recipient \"John Doe\" -r ''", "gpg").unwrap();
+        assert_eq!(encryptor, PassEncryptor::new("gpg", &["--encrypt", "--recipient", "John Doe", "-r", ""], "gpg"));
+        assert_eq!(split_command(r#"a"b c"'d'\ e "\"\x""#).unwrap(), ["ab cd e", "\"\\x"]);
+        assert!(PassEncryptor::from_command("  ", "gpg").is_none());
+        assert!(PassEncryptor::from_command("gpg 'John", "gpg").is_none());
+        assert!(split_command("gpg \\").is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_store_with_stub_command() {
+        let directory = std::env::temp_dir().join(format!("kg_passgen_pass_{}", std::process::id()));
+        let stub = PassEncryptor::new("sh", &["-c", "tr a-z n-za-m"], "gpg");
+        let entries = [entry("example.com", ""), entry("mail.com", "Mail"), entry("example.com", "Other")];
+
+        let paths = write_store(&directory, &entries, &stub).unwrap();
+        assert_eq!(paths, vec![directory.join("example.com.gpg"), directory.join("Mail").join("mail.com.gpg")]);
+        assert!(std::fs::read_to_string(&paths[0]).unwrap().starts_with("frperg\nhfreanzr: zr\n"));
+
+        let failing = PassEncryptor::new("sh", &["-c", "exit 2"], "gpg");
+        assert!(write_store(&directory, &entries, &failing).is_err());
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -21,7 +21,7 @@
 //! Returns an error if the length in the config is invalid for the selected hash algorithm
 //! ```
 //! use kg_passgen::config::{Config, HashAlgorithm, GeneratorType};
-//! use kg_passgen::generator::generate_password;
+//! use kg_passgen::generator::{generate_password, GenerationError};
 //! let config = Config::default()
 //!     .with_hash_algorithm(HashAlgorithm::MD5)
 //!    .with_length(30) // invalid length for MD5
@@ -29,13 +29,21 @@
 //! 
 //! let result = generate_password("https://example.com", "master", &config);
 //! assert!(result.is_err());
-//! assert!(matches!(result, Err(InvalidLengthError)));
+//! assert!(matches!(result, Err(GenerationError::InvalidLength)));
 //! ```
 
 use core::fmt;
 
 use base64::Engine;
 use crate::config::{Config, HashAlgorithm, GeneratorType};
+use crate::template::{Template, TemplateError};
+
+pub mod lesspass;
+pub mod passwordmaker;
+pub mod pronounceable;
+pub mod spectre;
+
+const DERIVATION_ITERATIONS: u32 = 100_000;
 
 /// Custom error type for invalid length configurations
 #[derive(Debug, Clone)]
@@ -47,6 +55,39 @@ impl fmt::Display for InvalidLengthError {
     }
 }
 
+/// Custom error type for configs that cannot generate a password
+#[derive(Debug, Clone, PartialEq)]
+pub enum GenerationError {
+    /// The length does not fit the selected hash algorithm or generator
+    InvalidLength,
+    /// The template does not fit the selected hash algorithm or hops
+    Template(TemplateError),
+    /// Spectre profiles need the full name of the user, it is part of the key derivation
+    MissingFullName,
+}
+
+impl fmt::Display for GenerationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerationError::InvalidLength => write!(f, "{}", InvalidLengthError),
+            GenerationError::Template(e) => write!(f, "{}", e),
+            GenerationError::MissingFullName => write!(f, "Spectre needs the full name of the user"),
+        }
+    }
+}
+
+impl From<InvalidLengthError> for GenerationError {
+    fn from(_: InvalidLengthError) -> Self {
+        GenerationError::InvalidLength
+    }
+}
+
+impl From<TemplateError> for GenerationError {
+    fn from(e: TemplateError) -> Self {
+        GenerationError::Template(e)
+    }
+}
+
 
 /// Hashes the input string using MD5 and encodes the result in Base64
 pub fn hash_md5(input: &str) -> String {
@@ -64,6 +105,176 @@ pub fn hash_sha512(input: &str) -> String {
     base64::prelude::BASE64_STANDARD.encode(result)
 }
 
+/// Passwords of configs with less estimated entropy are reported as weak by the CLI
+pub const RECOMMENDED_ENTROPY_BITS: f64 = 64.0;
+
+/// Entropy in bits of one of `size` characters picked with `value % size` from a value uniform over `0..range`.
+/// When `size` does not divide `range` the first `range % size` characters are more likely, so it is below `log2(size)`.
+/// # Examples
+/// ```
+/// use kg_passgen::generator::modulo_entropy_bits;
+/// assert_eq!(modulo_entropy_bits(256, 16), 4.0);
+/// assert!(modulo_entropy_bits(64, 10) < 10f64.log2());
+/// ```
+pub fn modulo_entropy_bits(range: usize, size: usize) -> f64 {
+    let (quotient, remainder) = (range / size, range % size);
+    [(remainder, quotient + 1), (size - remainder, quotient)].iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|&(characters, count)| {
+            let probability = count as f64 / range as f64;
+            -(characters as f64) * probability * probability.log2()
+        })
+        .sum()
+}
+
+/// Rules checked by `validate_password`
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Rule {
+    /// The password is at least as long as the config length, KGPG and SGP also require 8 characters
+    Length,
+    /// KGPG and SGP passwords start with a lowercase letter
+    StartsWithLowercase,
+    ContainsUppercase,
+    ContainsDigit,
+    /// KGPG and SGP passwords only contain letters, digits and `#?!@$%^&*`
+    AllowedCharacters,
+    /// KGPG passwords contain one of `!#%@$&`
+    ContainsSymbol,
+    /// LessPass passwords contain one character of each enabled set and nothing outside of them
+    CharacterSets,
+    /// Spectre passwords match one of the templates of the profile, templated passwords match `Config::template`
+    Template,
+    /// Pronounceable passwords are consonant-vowel syllables followed by the digits and symbol of the profile
+    Syllables,
+}
+
+/// Outcome of one rule of the validation
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RuleResult {
+    pub rule: Rule,
+    pub passed: bool,
+}
+
+/// Which rules a password passed or failed, and the entropy of the passwords of the config
+#[derive(Debug, PartialEq, Clone)]
+pub struct ValidationReport {
+    pub rules: Vec<RuleResult>,
+    /// Estimated entropy in bits of passwords generated with the config, see `entropy_bits`
+    pub entropy_bits: f64,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.rules.iter().all(|result| result.passed)
+    }
+
+    /// Returns the rules the password failed
+    pub fn failed(&self) -> Vec<Rule> {
+        self.rules.iter().filter(|result| !result.passed).map(|result| result.rule).collect()
+    }
+}
+
+/// Estimates the entropy in bits of the passwords generated with the config,
+/// from the alphabet and length of the generator and capped by the size of its hash.
+/// KGPG and SGP passwords always start with a lowercase letter, which is accounted for.
+/// # Examples
+/// ```
+/// use kg_passgen::config::{Config, HashAlgorithm};
+/// use kg_passgen::generator::entropy_bits;
+/// assert!(entropy_bits(&Config::KGPG) > 85.0);
+/// assert_eq!(entropy_bits(&Config::KGPG.with_hash_algorithm(HashAlgorithm::MD5).with_length(24)), 128.0);
+/// ```
+pub fn entropy_bits(config: &Config) -> f64 {
+    let length = config.length as f64;
+    let hash_bits = match config.hash_algorithm {
+        HashAlgorithm::MD5 => 128.0,
+        HashAlgorithm::SHA512 => 512.0,
+    };
+    if let Some(template) = hop_template(config) {
+        return template.entropy_bits().min(hash_bits);
+    }
+    let bits = match &config.generator_type {
+        // base64 characters map one to one, the first one is one of 26 lowercase letters
+        GeneratorType::KGPG => 26f64.log2() + (length - 1.0) * 6.0,
+        // '+' and '/' become '8' and '9', so two of the 62 characters are twice as likely
+        GeneratorType::SGP => 26f64.log2() + (length - 1.0) * (60.0 / 64.0 * 6.0 + 2.0 * 2.0 / 64.0 * 5.0),
+        GeneratorType::LessPass(profile) => return lesspass::entropy_bits(config.length, profile),
+        GeneratorType::Spectre(profile) => return spectre::entropy_bits(profile.template),
+        GeneratorType::PasswordMaker(profile) => return passwordmaker::entropy_bits(config.length, profile),
+        GeneratorType::Pronounceable(profile) => pronounceable::entropy_bits(config.length, profile),
+    };
+    bits.clamp(0.0, hash_bits)
+}
+
+/// Checks the password against every rule of the generator of the config.
+/// # Examples
+/// ```
+/// use kg_passgen::config::Config;
+/// use kg_passgen::generator::{validate_password_report, Rule};
+/// let report = validate_password_report("Abcdefgh1234567", &Config::KGPG);
+/// assert_eq!(report.failed(), vec![Rule::StartsWithLowercase, Rule::ContainsSymbol]);
+/// assert!(!report.is_valid());
+/// ```
+pub fn validate_password_report(password: &str, config: &Config) -> ValidationReport {
+    let entropy_bits = entropy_bits(config);
+    let result = |rule: Rule, passed: bool| RuleResult { rule, passed };
+
+    if let Some(template) = hop_template(config) {
+        let rules = vec![result(Rule::Template, template.matches(password))];
+        return ValidationReport { rules, entropy_bits };
+    }
+    if let GeneratorType::Spectre(profile) = &config.generator_type {
+        let rules = vec![result(Rule::Template, spectre::validate_password(password, profile.template))];
+        return ValidationReport { rules, entropy_bits };
+    }
+    if let GeneratorType::PasswordMaker(_) = &config.generator_type {
+        let rules = vec![result(Rule::Length, password.chars().count() == config.length as usize)];
+        return ValidationReport { rules, entropy_bits };
+    }
+
+    if let GeneratorType::Pronounceable(profile) = &config.generator_type {
+        let mut rules = vec![
+            result(Rule::Length, password.len() == config.length as usize),
+            result(Rule::Syllables, pronounceable::validate_password(password, profile)),
+        ];
+        if profile.capitalize {
+            rules.push(result(Rule::ContainsUppercase, password.chars().any(|c| c.is_ascii_uppercase())));
+        }
+        if profile.digits > 0 {
+            rules.push(result(Rule::ContainsDigit, password.chars().any(|c| c.is_ascii_digit())));
+        }
+        if profile.symbol {
+            rules.push(result(Rule::ContainsSymbol, password.chars().any(|c| "!#%@$&".contains(c))));
+        }
+        return ValidationReport { rules, entropy_bits };
+    }
+
+    let sliced_password = password.get(0..config.length as usize);
+    if let GeneratorType::LessPass(profile) = &config.generator_type {
+        let rules = vec![
+            result(Rule::Length, sliced_password.is_some()),
+            result(Rule::CharacterSets, sliced_password.is_some_and(|sliced| lesspass::validate_password(sliced, profile))),
+        ];
+        return ValidationReport { rules, entropy_bits };
+    }
+
+    let matches = |pattern: &str| {
+        let regex = fancy_regex::Regex::new(pattern).unwrap();
+        sliced_password.is_some_and(|sliced| regex.is_match(sliced).unwrap())
+    };
+    let mut rules = vec![
+        result(Rule::Length, sliced_password.is_some_and(|sliced| sliced.len() >= 8)),
+        result(Rule::StartsWithLowercase, matches(r"^[a-z]")),
+        result(Rule::ContainsUppercase, matches(r"[A-Z]")),
+        result(Rule::ContainsDigit, matches(r"[0-9]")),
+        result(Rule::AllowedCharacters, matches(r"^[a-zA-Z0-9#?!@$%^&*]*$")),
+    ];
+    if config.generator_type == GeneratorType::KGPG {
+        rules.push(result(Rule::ContainsSymbol, matches(r"[!#%@$&]")));
+    }
+    ValidationReport { rules, entropy_bits }
+}
+
 /// Validates that the generated password meets the required criteria
 /// KGPG requires at least one special character from the set !#%@$&, 
 /// as well as one uppercase letter, one lowercase letter, and one digit.
@@ -72,6 +283,13 @@ pub fn hash_sha512(input: &str) -> String {
 /// SGP requires at least one uppercase letter, one lowercase letter, and one digit.
 /// It cannot start with an uppercase letter.
 /// Additionally it validates the length of the generated password based on the config.
+/// LessPass requires one character of each enabled character set and nothing outside of them.
+/// Spectre requires the password to match one of the templates of the profile, the config length is ignored.
+/// PasswordMaker only requires the password to have as many characters as the config length.
+/// Pronounceable requires syllables followed by the digits and symbol of the profile,
+/// and an uppercase first letter when the profile capitalizes.
+/// With a template, KGPG, SGP and Pronounceable only require every character to match its slot.
+/// See `validate_password_report` for the rules that failed.
 /// # Examples
 /// ```
 /// use kg_passgen::config::{Config, HashAlgorithm, GeneratorType};
@@ -89,24 +307,7 @@ pub fn hash_sha512(input: &str) -> String {
 /// assert!(!validate_password("abcdefgfsadg", &config));
 /// ```
 pub fn validate_password(password: &str, config: &Config) -> bool {
-    let sliced_password = match password.get(0..config.length as usize) {
-        Some(slice) => slice,
-        None => return false,
-    };
-
-    let password_regex = fancy_regex::Regex::new(r"(?=.*^[a-z])(?=.*[A-Z])(?=.*[0-9])([a-zA-Z0-9#?!@$%^&*]){8,}$").unwrap();
-    if !password_regex.is_match(sliced_password).unwrap() {
-        return false;
-    }
-
-    if config.generator_type == GeneratorType::KGPG {
-        let special_char_regex = fancy_regex::Regex::new(r"[!#%@$&]").unwrap();
-        if !special_char_regex.is_match(sliced_password).unwrap() {
-            return false;
-        }
-    }
-
-    true
+    validate_password_report(password, config).is_valid()
 }
 
 /// Applies KGPG-specific character replacements
@@ -140,15 +341,39 @@ pub fn apply_sgp (password: &str) -> String {
     sgp_password
 }
 
+/// Returns the template of hop based configs, the other generators render their own passwords
+fn hop_template(config: &Config) -> Option<&Template> {
+    match config.generator_type {
+        GeneratorType::KGPG | GeneratorType::SGP | GeneratorType::Pronounceable(_) => config.template.as_ref(),
+        _ => None,
+    }
+}
+
+/// Turns the last hop into the password, only templated and Pronounceable configs render it.
+/// Templates take one byte of the digest per random slot, see `template`.
+fn render_hop(hop: &str, config: &Config) -> Result<String, GenerationError> {
+    if let Some(template) = hop_template(config) {
+        let digest = base64::prelude::BASE64_STANDARD.decode(hop).map_err(|_| TemplateError("the template needs at least one hop".to_string()))?;
+        return template.fill(&digest).ok_or_else(|| TemplateError("the digest is too short for the template".to_string()).into());
+    }
+    match &config.generator_type {
+        GeneratorType::Pronounceable(profile) => Ok(pronounceable::render_password(hop, config.length, profile)?),
+        _ => Ok(hop.to_string()),
+    }
+}
+
 /// Applies the password generation logic based on a single concatenated input
 /// For the KGPG and SGP algorithms, it expects a password in the format "master_password:host"
-pub fn apply_password_hops (password: &str, config: &Config) -> Result<String, InvalidLengthError> {
+pub fn apply_password_hops (password: &str, config: &Config) -> Result<String, GenerationError> {
     let mut hopped_password = password.to_string();
     let mut iteration = 0;
     let md5_length_invalid = config.hash_algorithm == HashAlgorithm::MD5 && (config.length < 8 || config.length > 24);
     let sha512_length_invalid = config.hash_algorithm == HashAlgorithm::SHA512 && (config.length < 8 || config.length > 84);
-    if md5_length_invalid || sha512_length_invalid {
-        return Err(InvalidLengthError);
+    // The template defines the length, its slots are checked against the digest instead
+    match hop_template(config) {
+        Some(template) => template.check_hash(&config.hash_algorithm)?,
+        None if md5_length_invalid || sha512_length_invalid => return Err(GenerationError::InvalidLength),
+        None => {},
     }
     while iteration < config.hops {
         hopped_password = match config.hash_algorithm {
@@ -157,15 +382,30 @@ pub fn apply_password_hops (password: &str, config: &Config) -> Result<String, I
         };
 
         hopped_password = match config.generator_type {
+            // Templates fill their slots from the plain digest, see `render_hop`
+            _ if hop_template(config).is_some() => hopped_password,
             GeneratorType::KGPG => apply_kgpg(&hopped_password),
             GeneratorType::SGP => apply_sgp(&hopped_password),
+            // LessPass is not hop based, see `generator::lesspass`
+            GeneratorType::LessPass(_) => hopped_password,
+            // Spectre is not hop based, see `generator::spectre`
+            GeneratorType::Spectre(_) => hopped_password,
+            // PasswordMaker is not hop based, see `generator::passwordmaker`
+            GeneratorType::PasswordMaker(_) => hopped_password,
+            // Pronounceable passwords are rendered from the last hop, see `render_hop`
+            GeneratorType::Pronounceable(_) => hopped_password,
         };
 
-        if iteration == config.hops - 1 && !validate_password(&hopped_password, config) {
-            iteration -= 1;
+        // Keep hashing the last hop until it validates
+        if iteration == config.hops - 1 && !validate_password(&render_hop(&hopped_password, config)?, config) {
+            continue;
         }
         iteration += 1;
     }
+    let hopped_password = render_hop(&hopped_password, config)?;
+    if hop_template(config).is_some() {
+        return Ok(hopped_password);
+    }
 
 
     let sliced_password = match hopped_password.get(0..config.length as usize) {
@@ -176,7 +416,43 @@ pub fn apply_password_hops (password: &str, config: &Config) -> Result<String, I
     Ok(sliced_password.to_string())
 }
 
+/// Derives 64 bytes for anything other than a password, such as usernames, TOTP secrets, keys and answers.
+/// The label separates each purpose, so the outputs cannot be linked to each other or to `generate_password`.
+/// Hosts come from `url_helper::get_config_host`, normalized the same way as for `generate_password`.
+/// PBKDF2-SHA512 is used since these outputs are often public and must not make the master password cheap to brute force.
+/// # Examples
+/// ```
+/// use kg_passgen::generator::derive_bytes;
+/// let username_bytes = derive_bytes("username", "my_master_password", "example.com");
+/// let other_bytes = derive_bytes("other", "my_master_password", "example.com");
+/// assert_ne!(username_bytes, other_bytes);
+/// ```
+pub fn derive_bytes(label: &str, master_password: &str, host: &str) -> [u8; 64] {
+    let salt = format!("kg_passgen:{}:{}", label, host.trim());
+    let mut output = [0u8; 64];
+    pbkdf2::pbkdf2_hmac::<sha2::Sha512>(master_password.trim().as_bytes(), salt.as_bytes(), DERIVATION_ITERATIONS, &mut output);
+    output
+}
+
+/// Derives the secret of an age X25519 identity for the key label, see `encryption` and `keys`.
+/// Profile stores and exports are encrypted with the empty label.
+/// Uses the `age-identity` label of `derive_bytes`.
+/// # Examples
+/// ```
+/// use kg_passgen::generator::{derive_age_secret, derive_bytes};
+/// let secret = derive_age_secret("my_master_password", "");
+/// assert_eq!(secret, derive_age_secret("my_master_password", ""));
+/// assert_ne!(secret, derive_age_secret("my_master_password", "laptop"));
+/// assert_ne!(secret[..], derive_bytes("username", "my_master_password", "")[..32]);
+/// ```
+pub fn derive_age_secret(master_password: &str, label: &str) -> [u8; 32] {
+    derive_bytes("age-identity", master_password, label)[..32].try_into().unwrap()
+}
+
 /// Main function for generating a password
+/// LessPass and Spectre configs are delegated to `generator::lesspass` and `generator::spectre`
+/// using the normalized host as the site.
+/// PasswordMaker configs are delegated to `generator::passwordmaker` using the URL components of the profile.
 /// # Examples
 /// ```
 /// use kg_passgen::config::{Config, HashAlgorithm, GeneratorType};
@@ -191,9 +467,21 @@ pub fn apply_password_hops (password: &str, config: &Config) -> Result<String, I
 /// assert_eq!(different_password.len(), config.length as usize);
 /// assert_eq!(different_password, "jtNRe$VWbnE#F6y");
 /// ```
-pub fn generate_password(url: &str, master_password: &str, config: &Config) -> Result<String, InvalidLengthError> {
+pub fn generate_password(url: &str, master_password: &str, config: &Config) -> Result<String, GenerationError> {
+    if let GeneratorType::PasswordMaker(profile) = &config.generator_type {
+        let url_components = crate::url_helper::get_url_components(url.trim(), &profile.url_components);
+        return Ok(passwordmaker::generate_password(&url_components, master_password, config.length, profile)?);
+    }
+
     // Placeholder for password generation logic
-    let host =  crate::url_helper::get_host(url, &config.strip_subdomain);
+    let host =  crate::url_helper::get_config_host(url, config);
+
+    if let GeneratorType::LessPass(profile) = &config.generator_type {
+        return Ok(lesspass::generate_password(host.trim(), master_password, config.length, profile)?);
+    }
+    if let GeneratorType::Spectre(profile) = &config.generator_type {
+        return spectre::generate_password(host.trim(), master_password, profile);
+    }
 
     let concat =format!("{}:{}", master_password.trim(), host.trim());
     apply_password_hops(&concat, config)
@@ -0,0 +1,166 @@
+//! LessPass compatible password generation.
+//! Derives 32 bytes of entropy with PBKDF2-SHA256 over the site, login and counter,
+//! then renders the password from the enabled character sets, making sure it
+//! contains at least one character of each enabled set.
+//!
+//! # Examples
+//! ```
+//! use kg_passgen::config::{Config, GeneratorType, LessPassProfile};
+//! use kg_passgen::generator::generate_password;
+//! let profile = LessPassProfile::default().with_login("contact@example.org");
+//! let config = Config::LESSPASS.with_generator_type(GeneratorType::LessPass(profile));
+//!
+//! let password = generate_password("https://example.org", "password", &config).unwrap();
+//! assert_eq!(password, "WHLpUL)e00[iHR+w");
+//! ```
+
+use sha2::Sha256;
+
+use crate::config::LessPassProfile;
+use super::InvalidLengthError;
+
+pub const MIN_LENGTH: u8 = 5;
+pub const MAX_LENGTH: u8 = 35;
+const ITERATIONS: u32 = 100_000;
+
+const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+const SYMBOLS: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+/// Returns the character sets enabled in the profile, in LessPass rule order
+pub fn character_sets(profile: &LessPassProfile) -> Vec<&'static str> {
+    [
+        (profile.lowercase, LOWERCASE),
+        (profile.uppercase, UPPERCASE),
+        (profile.digits, DIGITS),
+        (profile.symbols, SYMBOLS),
+    ]
+    .into_iter()
+    .filter(|(enabled, _)| *enabled)
+    .map(|(_, set)| set)
+    .collect()
+}
+
+/// Derives the LessPass entropy for a site, login and counter.
+/// The salt is the site, the login and the counter in lowercase hex concatenated together.
+pub fn calc_entropy(site: &str, login: &str, master_password: &str, counter: u32) -> [u8; 32] {
+    let salt = format!("{}{}{:x}", site, login, counter);
+    let mut entropy = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(master_password.as_bytes(), salt.as_bytes(), ITERATIONS, &mut entropy);
+    entropy
+}
+
+/// Divides the big-endian number in place and returns the remainder
+fn div_rem(number: &mut [u8], divisor: u32) -> usize {
+    let mut remainder: u32 = 0;
+    for byte in number.iter_mut() {
+        let current = (remainder << 8) | *byte as u32;
+        *byte = (current / divisor) as u8;
+        remainder = current % divisor;
+    }
+    remainder as usize
+}
+
+/// Appends characters picked from the set until the password reaches the length
+fn consume_entropy(password: &mut Vec<char>, entropy: &mut [u8], set: &[char], length: usize) {
+    while password.len() < length {
+        let index = div_rem(entropy, set.len() as u32);
+        password.push(set[index]);
+    }
+}
+
+/// Renders a password from the entropy following the LessPass rules
+pub fn render_password(entropy: &[u8; 32], length: u8, profile: &LessPassProfile) -> Result<String, InvalidLengthError> {
+    let sets = character_sets(profile);
+    if sets.is_empty() || !(MIN_LENGTH..=MAX_LENGTH).contains(&length) {
+        return Err(InvalidLengthError);
+    }
+
+    let mut quotient = *entropy;
+    let all_characters: Vec<char> = sets.concat().chars().collect();
+    let mut password = Vec::new();
+    consume_entropy(&mut password, &mut quotient, &all_characters, length as usize - sets.len());
+
+    let mut one_char_per_rule = Vec::new();
+    for set in &sets {
+        let set: Vec<char> = set.chars().collect();
+        let mut character = Vec::new();
+        consume_entropy(&mut character, &mut quotient, &set, 1);
+        one_char_per_rule.extend(character);
+    }
+
+    for character in one_char_per_rule {
+        let index = div_rem(&mut quotient, password.len() as u32);
+        password.insert(index, character);
+    }
+
+    Ok(password.into_iter().collect())
+}
+
+/// Checks that the password has one character of each enabled set and nothing outside of them
+pub fn validate_password(password: &str, profile: &LessPassProfile) -> bool {
+    let sets = character_sets(profile);
+    let all_allowed = password.chars().all(|c| sets.iter().any(|set| set.contains(c)));
+    let one_per_set = sets.iter().all(|set| password.chars().any(|c| set.contains(c)));
+    !sets.is_empty() && all_allowed && one_per_set
+}
+
+/// Estimates the entropy in bits of the passwords, capped by the 256 bits of derived entropy
+pub fn entropy_bits(length: u8, profile: &LessPassProfile) -> f64 {
+    let alphabet: usize = character_sets(profile).iter().map(|set| set.len()).sum();
+    if alphabet == 0 {
+        return 0.0;
+    }
+    (length as f64 * (alphabet as f64).log2()).min(256.0)
+}
+
+/// Generates a LessPass password for an already normalized site
+pub fn generate_password(site: &str, master_password: &str, length: u8, profile: &LessPassProfile) -> Result<String, InvalidLengthError> {
+    let entropy = calc_entropy(site, &profile.login, master_password, profile.counter);
+    render_password(&entropy, length, profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_calc_entropy_matches_lesspass() {
+        let entropy = calc_entropy("example.org", "contact@example.org", "password", 1);
+        assert_eq!(to_hex(&entropy), "dc33d431bce2b01182c613382483ccdb0e2f66482cbba5e9d07dab34acc7eb1e");
+    }
+
+    #[test]
+    fn test_generate_password_matches_lesspass() {
+        let profile = LessPassProfile::default().with_login("contact@example.org");
+        let password = generate_password("example.org", "password", 16, &profile).unwrap();
+        assert_eq!(password, "WHLpUL)e00[iHR+w");
+    }
+
+    #[test]
+    fn test_render_password_contains_one_char_per_rule() {
+        let profile = LessPassProfile::default().with_lowercase(false).with_symbols(false);
+        let password = render_password(&[7u8; 32], 5, &profile).unwrap();
+        assert_eq!(password.len(), 5);
+        assert!(password.chars().any(|c| c.is_ascii_uppercase()));
+        assert!(password.chars().any(|c| c.is_ascii_digit()));
+        assert!(password.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()));
+        assert!(validate_password(&password, &profile));
+        assert!(!validate_password("ABCDE", &profile));
+    }
+
+    #[test]
+    fn test_render_password_rejects_invalid_profiles() {
+        let profile = LessPassProfile::default();
+        assert!(render_password(&[0u8; 32], 4, &profile).is_err());
+        assert!(render_password(&[0u8; 32], 36, &profile).is_err());
+
+        let empty = profile.with_lowercase(false).with_uppercase(false).with_digits(false).with_symbols(false);
+        assert!(render_password(&[0u8; 32], 16, &empty).is_err());
+    }
+}
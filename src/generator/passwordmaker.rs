@@ -0,0 +1,217 @@
+//! PasswordMaker Pro compatible password generation.
+//! The selected URL components, username and modifier are hashed with the master password,
+//! optionally as an HMAC, and the digest is rendered in the base of the character set.
+//! More hashes are appended with the key `master_password\ncount` until the password is long enough,
+//! then the prefix and suffix are applied.
+//! The MD5 version 0.6 compatibility algorithms of PasswordMaker Pro are not supported.
+//!
+//! # Examples
+//! ```
+//! use kg_passgen::config::Config;
+//! use kg_passgen::generator::generate_password;
+//! let config = Config::PASSWORDMAKER;
+//!
+//! let password = generate_password("https://www.example.com/login", "my_master_password", &config).unwrap();
+//! assert_eq!(password.chars().count(), 8);
+//! assert_eq!(password, generate_password("example.com", "my_master_password", &config).unwrap());
+//! ```
+
+use md4::Md4;
+use ripemd::Ripemd160;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+use crate::config::{LeetMode, PasswordMakerAlgorithm, PasswordMakerProfile};
+use super::InvalidLengthError;
+
+const BLOCK_SIZE: usize = 64;
+
+const LEET_LEVELS: [[&str; 26]; 9] = [
+    ["4", "b", "c", "d", "3", "f", "g", "h", "i", "j", "k", "1", "m", "n", "0", "p", "9", "r", "s", "7", "u", "v", "w", "x", "y", "z"],
+    ["4", "b", "c", "d", "3", "f", "g", "h", "1", "j", "k", "1", "m", "n", "0", "p", "9", "r", "5", "7", "u", "v", "w", "x", "y", "2"],
+    ["4", "8", "c", "d", "3", "f", "6", "h", "'", "j", "k", "1", "m", "n", "0", "p", "9", "r", "5", "7", "u", "v", "w", "x", "'/", "2"],
+    ["@", "8", "c", "d", "3", "f", "6", "h", "'", "j", "k", "1", "m", "n", "0", "p", "9", "r", "5", "7", "u", "v", "w", "x", "'/", "2"],
+    ["@", "|3", "c", "d", "3", "f", "6", "#", "!", "7", "|<", "1", "m", "n", "0", "|>", "9", "|2", "$", "7", "u", "\\/", "w", "x", "'/", "2"],
+    ["@", "|3", "c", "|)", "&", "|=", "6", "#", "!", ",|", "|<", "1", "m", "n", "0", "|>", "9", "|2", "$", "7", "u", "\\/", "w", "x", "'/", "2"],
+    ["@", "|3", "[", "|)", "&", "|=", "6", "#", "!", ",|", "|<", "1", "^^", "^/", "0", "|*", "9", "|2", "5", "7", "(_)", "\\/", "\\/\\/", "><", "'/", "2"],
+    ["@", "8", "(", "|)", "&", "|=", "6", "|-|", "!", "_|", "|(", "1", "|\\/|", "|\\|", "()", "|>", "(,)", "|2", "$", "|", "|_|", "\\/", "\\^/", ")(", "'/", "\"/_"],
+    ["@", "8", "(", "|)", "&", "|=", "6", "|-|", "!", "_|", "|{", "|_", "/\\/\\", "|\\|", "()", "|>", "(,)", "|2", "$", "|", "|_|", "\\/", "\\^/", ")(", "'/", "\"/_"],
+];
+
+/// Lowercases the input and applies the l33t replacements of the level (1 to 9)
+pub fn leetify(input: &str, level: u8) -> String {
+    let table = &LEET_LEVELS[(level.clamp(1, 9) - 1) as usize];
+    let mut leet = String::new();
+    for c in input.to_lowercase().chars() {
+        match c {
+            'a'..='z' => leet.push_str(table[(c as u8 - b'a') as usize]),
+            _ => leet.push(c),
+        }
+    }
+    leet
+}
+
+/// Hashes the input with the selected algorithm
+fn hash(algorithm: PasswordMakerAlgorithm, input: &[u8]) -> Vec<u8> {
+    match algorithm {
+        PasswordMakerAlgorithm::MD4 => Md4::digest(input).to_vec(),
+        PasswordMakerAlgorithm::MD5 => md5::compute(input).0.to_vec(),
+        PasswordMakerAlgorithm::SHA1 => Sha1::digest(input).to_vec(),
+        PasswordMakerAlgorithm::SHA256 => Sha256::digest(input).to_vec(),
+        PasswordMakerAlgorithm::RIPEMD160 => Ripemd160::digest(input).to_vec(),
+    }
+}
+
+/// Computes the HMAC of the data, all supported algorithms use 64 byte blocks
+fn hmac(algorithm: PasswordMakerAlgorithm, key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut block = if key.len() > BLOCK_SIZE { hash(algorithm, key) } else { key.to_vec() };
+    block.resize(BLOCK_SIZE, 0);
+
+    let mut inner: Vec<u8> = block.iter().map(|b| b ^ 0x36).collect();
+    inner.extend_from_slice(data);
+    let mut outer: Vec<u8> = block.iter().map(|b| b ^ 0x5c).collect();
+    outer.extend(hash(algorithm, &inner));
+    hash(algorithm, &outer)
+}
+
+/// Converts the big-endian digest to digits of the base, most significant first
+fn to_base(digest: &[u8], base: usize) -> Vec<usize> {
+    let mut number = digest.to_vec();
+    let mut digits = Vec::new();
+    while number.iter().any(|b| *b != 0) {
+        let mut remainder = 0usize;
+        for byte in number.iter_mut() {
+            let current = (remainder << 8) | *byte as usize;
+            *byte = (current / base) as u8;
+            remainder = current % base;
+        }
+        digits.push(remainder);
+    }
+    digits.reverse();
+    digits
+}
+
+/// Generates one part of the password from a single hash
+fn password_part(data: &str, key: &str, profile: &PasswordMakerProfile, characters: &[char]) -> String {
+    let leet_before = matches!(profile.leet_mode, LeetMode::BeforeHashing | LeetMode::Both);
+    let leet_after = matches!(profile.leet_mode, LeetMode::AfterHashing | LeetMode::Both);
+
+    let digest = if profile.hmac {
+        let (key, data) = if leet_before {
+            (leetify(key, profile.leet_level), leetify(data, profile.leet_level))
+        } else {
+            (key.to_string(), data.to_string())
+        };
+        hmac(profile.algorithm, key.as_bytes(), data.as_bytes())
+    } else {
+        let message = format!("{}{}", key, data);
+        let message = if leet_before { leetify(&message, profile.leet_level) } else { message };
+        hash(profile.algorithm, message.as_bytes())
+    };
+
+    let part: String = to_base(&digest, characters.len()).into_iter().map(|digit| characters[digit]).collect();
+    if leet_after { leetify(&part, profile.leet_level) } else { part }
+}
+
+/// Estimates the entropy in bits of the passwords, the prefix and suffix add none
+pub fn entropy_bits(length: u8, profile: &PasswordMakerProfile) -> f64 {
+    let characters = match profile.characters.chars().count() {
+        0 => PasswordMakerProfile::DEFAULT_CHARACTERS.chars().count(),
+        count => count,
+    };
+    let generated = (length as usize).saturating_sub(profile.prefix.chars().count() + profile.suffix.chars().count());
+    generated as f64 * (characters as f64).log2()
+}
+
+/// Generates a PasswordMaker password from the already recombined URL components
+pub fn generate_password(url_components: &str, master_password: &str, length: u8, profile: &PasswordMakerProfile) -> Result<String, InvalidLengthError> {
+    let characters: Vec<char> = if profile.characters.is_empty() {
+        PasswordMakerProfile::DEFAULT_CHARACTERS.chars().collect()
+    } else {
+        profile.characters.chars().collect()
+    };
+    if characters.len() < 2 || length == 0 {
+        return Err(InvalidLengthError);
+    }
+
+    let length = length as usize;
+    let suffix_length = profile.suffix.chars().count();
+    let data = format!("{}{}{}", url_components, profile.username, profile.modifier);
+
+    let mut password: Vec<char> = profile.prefix.chars().collect();
+    let mut count = 0;
+    while password.len() < length.saturating_sub(suffix_length) {
+        let key = if count == 0 { master_password.to_string() } else { format!("{}\n{}", master_password, count) };
+        password.extend(password_part(&data, &key, profile, &characters).chars());
+        count += 1;
+    }
+
+    password.truncate(length.saturating_sub(suffix_length));
+    password.extend(profile.suffix.chars());
+    password.truncate(length);
+    Ok(password.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA: &str = ".0123456789012345678901234567890123456789012345678901234567890123456789012345678901234567890123456789öä@€Whatever";
+    const KEY: &str = "0123456789012345678901234567890123456789012345678901234567890123456789012345678901234567890123456789";
+
+    fn vector_profile(algorithm: PasswordMakerAlgorithm, hmac: bool) -> PasswordMakerProfile {
+        PasswordMakerProfile::default()
+            .with_algorithm(algorithm)
+            .with_hmac(hmac)
+            .with_characters("ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789")
+            .with_username("max_mustermann")
+            .with_modifier("modification")
+            .with_prefix("pre")
+            .with_suffix("suf")
+            .with_leet(LeetMode::BeforeHashing, 9)
+    }
+
+    #[test]
+    fn test_default_profile_matches_passwordmaker() {
+        let password = generate_password(".abcdefghij", "1", 8, &PasswordMakerProfile::default()).unwrap();
+        assert_eq!(password, "J3>'1F\"/");
+    }
+
+    #[test]
+    fn test_leading_zeros_are_skipped() {
+        let profile = PasswordMakerProfile::default().with_characters("0123456789abcdef");
+        assert_eq!(generate_password("01", "a", 8, &profile).unwrap(), "d2a73551");
+    }
+
+    #[test]
+    fn test_each_algorithm_matches_passwordmaker() {
+        let vectors = [
+            (PasswordMakerAlgorithm::MD4, false, "preBhaY7RkI3FU2Dd1gGbkHGXHcaS9Rla5yOyBsxtRhYjUV4CoEKST1N73Ipmsuf"),
+            (PasswordMakerAlgorithm::MD4, true, "preCWxYmOtw9SouPQCHRRxLWODlFQ5LFitOpYMLHdnELniLHtQpdH5U2eOAOHsuf"),
+            (PasswordMakerAlgorithm::MD5, false, "preDPeKYAEZMwmB99f7i48vWApmx8ZLbz46s2nyG6KNO00G4nEElILxWAtGLGsuf"),
+            (PasswordMakerAlgorithm::MD5, true, "preGWR5UvFWn69uJQqedOi07JErUQfxJLLB3ZlLPjltwUI0HFDoN6p5xhGdd5suf"),
+            (PasswordMakerAlgorithm::SHA1, false, "preWiv8G0J7zvTurM7Mwjy7LeXKBfbJCqJtP6EOAR8dhgF8dFh6h3OCUybzwusuf"),
+            (PasswordMakerAlgorithm::SHA1, true, "prekLwEUc8ccgo6cK6nct1E58HZu3x5q0yCN8HHLVMx0QzjKMAfHCMyGeZxFEsuf"),
+            (PasswordMakerAlgorithm::SHA256, false, "pregtXH0tXq1nKXH6adaYb9dtXgCAFl2cqCPMQW3E7EeDggB5Oft4HaNdq5uRsuf"),
+            (PasswordMakerAlgorithm::SHA256, true, "pre5oyv5RXFzY0NiZF4b5JWQj5RUtotkI5dbJOeRJmSjpiYllu5ZZ8FXZqyY4suf"),
+            (PasswordMakerAlgorithm::RIPEMD160, false, "preFJeYiiAXx8Aa1Fhvyy0ffw7D9AMU2qKLg5BBjwZxyT6rsbHctS1Yv1PhGjsuf"),
+            (PasswordMakerAlgorithm::RIPEMD160, true, "preZ1zVB4UtRfvu6PhBvMPTkmAbX9WZ6Xzqb20OKFmKrFMfyF2eB4ImF2fhmWsuf"),
+        ];
+        for (algorithm, hmac, expected) in vectors {
+            let password = generate_password(DATA, KEY, 64, &vector_profile(algorithm, hmac)).unwrap();
+            assert_eq!(password, expected, "{:?} hmac: {}", algorithm, hmac);
+        }
+    }
+
+    #[test]
+    fn test_suffix_longer_than_length_is_truncated() {
+        let profile = vector_profile(PasswordMakerAlgorithm::RIPEMD160, true).with_suffix("suffix");
+        assert_eq!(generate_password(DATA, KEY, 5, &profile).unwrap(), "suffi");
+    }
+
+    #[test]
+    fn test_leetify_lowercases_and_replaces() {
+        assert_eq!(leetify("Hello World", 1), "h3110 w0r1d");
+        assert_eq!(leetify("abc", 9), "@8(");
+    }
+}
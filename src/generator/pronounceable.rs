@@ -0,0 +1,150 @@
+//! Pronounceable strings built from consonant-vowel syllables, easy to read out and type.
+//! `GeneratorType::Pronounceable` passwords are rendered from the last hop: each character takes one
+//! base64 character of the hash, the syllables come first, then the digits and symbol of the profile
+//! so the password still satisfies policies requiring them.
+//!
+//! # Examples
+//! ```
+//! use kg_passgen::config::Config;
+//! use kg_passgen::generator::{generate_password, validate_password};
+//! let config = Config::PRONOUNCEABLE;
+//!
+//! let password = generate_password("https://example.com", "my_master_password", &config).unwrap();
+//! assert_eq!(password.len(), config.length as usize);
+//! assert!(validate_password(&password, &config));
+//! assert_eq!(password, "Pakuzoheligag14$");
+//! ```
+
+use crate::config::PronounceableProfile;
+use super::{modulo_entropy_bits, InvalidLengthError};
+
+const CONSONANTS: &[u8; 16] = b"bdfghjklmnprstvz";
+const VOWELS: &[u8; 5] = b"aeiou";
+const DIGITS: &[u8; 10] = b"0123456789";
+/// The symbols of KGPG passwords
+const SYMBOLS: &[u8; 6] = b"!#%@$&";
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Renders one lowercase consonant-vowel syllable per pair of bytes, extra bytes are ignored.
+/// Each syllable carries about 6.3 bits from the base64 values of a hop, see `entropy_bits`.
+/// # Examples
+/// ```
+/// use kg_passgen::generator::pronounceable::syllables;
+/// assert_eq!(syllables(&[0, 0, 1, 1, 2]), "bade");
+/// ```
+pub fn syllables(bytes: &[u8]) -> String {
+    bytes.chunks_exact(2).flat_map(|pair| [
+        CONSONANTS[pair[0] as usize % CONSONANTS.len()] as char,
+        VOWELS[pair[1] as usize % VOWELS.len()] as char,
+    ]).collect()
+}
+
+/// Returns how many characters of the password are syllables, `None` when the suffix leaves less than two
+fn body_length(length: u8, profile: &PronounceableProfile) -> Option<usize> {
+    let suffix = profile.digits as usize + profile.symbol as usize;
+    (length as usize).checked_sub(suffix).filter(|body| *body >= 2)
+}
+
+/// Renders the password from a base64 hop, padding is skipped.
+/// Fails when the suffix leaves no room for a syllable or the hop is too short,
+/// so MD5 allows up to 22 characters.
+pub fn render_password(hop: &str, length: u8, profile: &PronounceableProfile) -> Result<String, InvalidLengthError> {
+    let body_length = body_length(length, profile).ok_or(InvalidLengthError)?;
+    let values: Vec<u8> = hop.bytes().filter_map(|c| BASE64_ALPHABET.iter().position(|&a| a == c).map(|value| value as u8)).collect();
+    if values.len() < (length as usize).max(body_length.div_ceil(2) * 2) {
+        return Err(InvalidLengthError);
+    }
+
+    let mut password: String = syllables(&values[..body_length.div_ceil(2) * 2]).chars().take(body_length).collect();
+    if profile.capitalize {
+        password[..1].make_ascii_uppercase();
+    }
+    let suffix = &values[body_length..length as usize];
+    for (index, value) in suffix.iter().enumerate() {
+        let set: &[u8] = if index < profile.digits as usize { DIGITS } else { SYMBOLS };
+        password.push(set[*value as usize % set.len()] as char);
+    }
+    Ok(password)
+}
+
+/// Checks that the password is syllables followed by the digits and symbol of the profile
+pub fn validate_password(password: &str, profile: &PronounceableProfile) -> bool {
+    let Some(body_length) = password.len().try_into().ok().and_then(|length| body_length(length, profile)) else {
+        return false;
+    };
+    let (body, suffix) = password.as_bytes().split_at(body_length);
+    let syllables_valid = body.iter().enumerate().all(|(index, c)| {
+        let set: &[u8] = if index % 2 == 0 { CONSONANTS } else { VOWELS };
+        let c = if index == 0 && profile.capitalize { c.to_ascii_lowercase() } else { *c };
+        set.contains(&c)
+    });
+    let suffix_valid = suffix.iter().enumerate().all(|(index, c)| {
+        if index < profile.digits as usize { DIGITS.contains(c) } else { SYMBOLS.contains(c) }
+    });
+    syllables_valid && suffix_valid && (!profile.capitalize || body[0].is_ascii_uppercase())
+}
+
+/// Estimates the entropy in bits of the passwords, capitalizing the first letter adds none.
+/// Each character takes a base64 value modulo the size of its set, so vowels, digits and symbols,
+/// whose sets do not divide 64, favour their first characters slightly.
+pub fn entropy_bits(length: u8, profile: &PronounceableProfile) -> f64 {
+    let Some(body_length) = body_length(length, profile) else {
+        return 0.0;
+    };
+    let bits = |set: &[u8]| modulo_entropy_bits(BASE64_ALPHABET.len(), set.len());
+    (body_length / 2) as f64 * (bits(CONSONANTS) + bits(VOWELS))
+        + (body_length % 2) as f64 * bits(CONSONANTS)
+        + profile.digits as f64 * bits(DIGITS)
+        + profile.symbol as u8 as f64 * bits(SYMBOLS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_password_layout() {
+        let hop = crate::generator::hash_sha512("master:example.com");
+        let profile = PronounceableProfile::default();
+        let password = render_password(&hop, 13, &profile).unwrap();
+        assert_eq!(password.len(), 13);
+        assert!(password.as_bytes()[0].is_ascii_uppercase());
+        assert!(password[10..12].chars().all(|c| c.is_ascii_digit()));
+        assert!(SYMBOLS.contains(&password.as_bytes()[12]));
+        assert!(validate_password(&password, &profile));
+
+        let plain = profile.with_capitalize(false).with_digits(0).with_symbol(false);
+        let password = render_password(&hop, 9, &plain).unwrap();
+        assert!(password.chars().all(|c| c.is_ascii_lowercase()));
+        assert!(validate_password(&password, &plain));
+    }
+
+    #[test]
+    fn test_render_password_rejects_invalid_lengths() {
+        let profile = PronounceableProfile::default().with_digits(7);
+        assert!(render_password(&crate::generator::hash_sha512("master"), 9, &profile).is_err());
+        assert!(render_password(&crate::generator::hash_md5("master"), 23, &PronounceableProfile::default()).is_err());
+        assert!(render_password(&crate::generator::hash_md5("master"), 22, &PronounceableProfile::default()).is_ok());
+    }
+
+    #[test]
+    fn test_entropy_bits_accounts_for_modulo_bias() {
+        let profile = PronounceableProfile::default();
+        let unbiased = 6.0 * (16f64.log2() + 5f64.log2()) + 16f64.log2() + 2.0 * 10f64.log2() + 6f64.log2();
+        assert!(entropy_bits(16, &profile) < unbiased);
+        assert!(entropy_bits(16, &profile) > unbiased - 0.1);
+        let consonants_only = profile.with_capitalize(false).with_digits(0).with_symbol(false);
+        assert_eq!(entropy_bits(9, &consonants_only) - entropy_bits(8, &consonants_only), 4.0);
+    }
+
+    #[test]
+    fn test_validate_password_checks_syllables_and_suffix() {
+        let profile = PronounceableProfile::default();
+        assert!(validate_password("Kabote12!", &profile));
+        assert!(!validate_password("kabote12!", &profile));
+        assert!(!validate_password("Kaabte12!", &profile));
+        assert!(!validate_password("Kabote1a!", &profile));
+        assert!(!validate_password("Kabote12a", &profile));
+        assert!(!validate_password("K1!", &profile));
+    }
+}
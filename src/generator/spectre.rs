@@ -0,0 +1,183 @@
+//! Spectre (formerly Master Password) compatible password generation, algorithm version 3.
+//! The user key is derived with scrypt from the full name and master password,
+//! the site key is an HMAC-SHA256 of the site name and counter, and the password
+//! is rendered from the template class selected in the profile.
+//!
+//! # Examples
+//! ```
+//! use kg_passgen::config::{Config, GeneratorType, SpectreProfile};
+//! use kg_passgen::generator::generate_password;
+//! let profile = SpectreProfile::default().with_full_name("Robert Lee Mitchell");
+//! let config = Config::SPECTRE.with_generator_type(GeneratorType::Spectre(profile));
+//!
+//! let password = generate_password("https://masterpasswordapp.com", "banana colored duckling", &config).unwrap();
+//! assert_eq!(password, "Jejr5[RepuSosp");
+//! ```
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::config::{SpectreProfile, SpectreTemplate};
+use super::GenerationError;
+
+const SCOPE: &str = "com.lyndir.masterpassword";
+
+/// Returns the templates available for a template class
+pub fn templates(template: SpectreTemplate) -> &'static [&'static str] {
+    match template {
+        SpectreTemplate::Maximum => &["anoxxxxxxxxxxxxxxxxx", "axxxxxxxxxxxxxxxxxno"],
+        SpectreTemplate::Long => &[
+            "CvcvnoCvcvCvcv", "CvcvCvcvnoCvcv", "CvcvCvcvCvcvno",
+            "CvccnoCvcvCvcv", "CvccCvcvnoCvcv", "CvccCvcvCvcvno",
+            "CvcvnoCvccCvcv", "CvcvCvccnoCvcv", "CvcvCvccCvcvno",
+            "CvcvnoCvcvCvcc", "CvcvCvcvnoCvcc", "CvcvCvcvCvccno",
+            "CvccnoCvccCvcv", "CvccCvccnoCvcv", "CvccCvccCvcvno",
+            "CvcvnoCvccCvcc", "CvcvCvccnoCvcc", "CvcvCvccCvccno",
+            "CvccnoCvcvCvcc", "CvccCvcvnoCvcc", "CvccCvcvCvccno",
+        ],
+        SpectreTemplate::Medium => &["CvcnoCvc", "CvcCvcno"],
+        SpectreTemplate::Short => &["Cvcn"],
+        SpectreTemplate::Basic => &["aaanaaan", "aannaaan", "aaannaaa"],
+        SpectreTemplate::PIN => &["nnnn"],
+        SpectreTemplate::Name => &["cvccvcvcv"],
+        SpectreTemplate::Phrase => &["cvcc cvc cvccvcv cvc", "cvc cvccvcvcv cvcv", "cv cvccv cvc cvcvccv"],
+    }
+}
+
+/// Returns the characters a template character class can be rendered as
+fn character_class(class: char) -> &'static str {
+    match class {
+        'V' => "AEIOU",
+        'C' => "BCDFGHJKLMNPQRSTVWXYZ",
+        'v' => "aeiou",
+        'c' => "bcdfghjklmnpqrstvwxyz",
+        'A' => "AEIOUBCDFGHJKLMNPQRSTVWXYZ",
+        'a' => "AEIOUaeiouBCDFGHJKLMNPQRSTVWXYZbcdfghjklmnpqrstvwxyz",
+        'n' => "0123456789",
+        'o' => "@&%?,=[]_:-+*$#!'^~;()/.",
+        'x' => "AEIOUaeiouBCDFGHJKLMNPQRSTVWXYZbcdfghjklmnpqrstvwxyz0123456789!@#$%^&*()",
+        _ => " ",
+    }
+}
+
+/// Prefixes the value with its length as a big-endian u32
+fn length_prefixed(value: &str) -> Vec<u8> {
+    let mut bytes = (value.len() as u32).to_be_bytes().to_vec();
+    bytes.extend_from_slice(value.as_bytes());
+    bytes
+}
+
+/// Derives the 64 byte user key from the full name and master password using scrypt
+pub fn user_key(full_name: &str, master_password: &str) -> [u8; 64] {
+    let mut salt = SCOPE.as_bytes().to_vec();
+    salt.extend(length_prefixed(full_name));
+
+    let params = scrypt::Params::new(15, 8, 2, 64).unwrap();
+    let mut key = [0u8; 64];
+    scrypt::scrypt(master_password.as_bytes(), &salt, &params, &mut key).unwrap();
+    key
+}
+
+/// Derives the site key from the user key, site name and counter
+pub fn site_key(user_key: &[u8; 64], site_name: &str, counter: u32) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(user_key).unwrap();
+    mac.update(SCOPE.as_bytes());
+    mac.update(&length_prefixed(site_name));
+    mac.update(&counter.to_be_bytes());
+    mac.finalize().into_bytes().into()
+}
+
+/// Renders the password for a template class from the site key
+pub fn render_password(site_key: &[u8; 32], template: SpectreTemplate) -> String {
+    let options = templates(template);
+    let selected = options[site_key[0] as usize % options.len()];
+
+    selected
+        .chars()
+        .enumerate()
+        .map(|(index, class)| {
+            let characters = character_class(class).as_bytes();
+            characters[site_key[index + 1] as usize % characters.len()] as char
+        })
+        .collect()
+}
+
+/// Checks that the password can be rendered by one of the templates of the class
+pub fn validate_password(password: &str, template: SpectreTemplate) -> bool {
+    templates(template).iter().any(|candidate| {
+        candidate.len() == password.len()
+            && candidate.chars().zip(password.chars()).all(|(class, c)| character_class(class).contains(c))
+    })
+}
+
+/// Estimates the entropy in bits of the passwords rendered for a template class
+pub fn entropy_bits(template: SpectreTemplate) -> f64 {
+    templates(template)
+        .iter()
+        .map(|candidate| candidate.chars().map(|class| character_class(class).len() as f64).product::<f64>())
+        .sum::<f64>()
+        .log2()
+}
+
+/// Generates a Spectre password for an already normalized site name, failing without a full name
+pub fn generate_password(site_name: &str, master_password: &str, profile: &SpectreProfile) -> Result<String, GenerationError> {
+    if profile.full_name.trim().is_empty() {
+        return Err(GenerationError::MissingFullName);
+    }
+    let user_key = user_key(&profile.full_name, master_password);
+    let site_key = site_key(&user_key, site_name, profile.counter);
+    Ok(render_password(&site_key, profile.template))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FULL_NAME: &str = "Robert Lee Mitchell";
+    const MASTER_PASSWORD: &str = "banana colored duckling";
+    const SITE_NAME: &str = "masterpasswordapp.com";
+
+    #[test]
+    fn test_render_password_matches_published_vectors() {
+        let user_key = user_key(FULL_NAME, MASTER_PASSWORD);
+        let site_key = site_key(&user_key, SITE_NAME, 1);
+
+        let vectors = [
+            (SpectreTemplate::Maximum, "W6@692^B1#&@gVdSdLZ@"),
+            (SpectreTemplate::Long, "Jejr5[RepuSosp"),
+            (SpectreTemplate::Medium, "Jej2$Quv"),
+            (SpectreTemplate::Short, "Jej2"),
+            (SpectreTemplate::Basic, "WAo2xIg6"),
+            (SpectreTemplate::PIN, "7662"),
+            (SpectreTemplate::Name, "jejraquvo"),
+            (SpectreTemplate::Phrase, "jejr quv cabsibu tam"),
+        ];
+        for (template, expected) in vectors {
+            let password = render_password(&site_key, template);
+            assert_eq!(password, expected, "{:?}", template);
+            assert!(validate_password(&password, template));
+        }
+    }
+
+    #[test]
+    fn test_counter_changes_password() {
+        let user_key = user_key(FULL_NAME, MASTER_PASSWORD);
+        let first = render_password(&site_key(&user_key, SITE_NAME, 1), SpectreTemplate::Long);
+        let second = render_password(&site_key(&user_key, SITE_NAME, 2), SpectreTemplate::Long);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_generate_password_requires_a_full_name() {
+        assert!(matches!(generate_password(SITE_NAME, MASTER_PASSWORD, &SpectreProfile::default()), Err(GenerationError::MissingFullName)));
+        assert!(generate_password(SITE_NAME, MASTER_PASSWORD, &SpectreProfile::default().with_full_name(" ")).is_err());
+        assert_eq!(generate_password(SITE_NAME, MASTER_PASSWORD, &SpectreProfile::default().with_full_name(FULL_NAME)).unwrap(), "Jejr5[RepuSosp");
+    }
+
+    #[test]
+    fn test_validate_password_rejects_other_templates() {
+        assert!(validate_password("1234", SpectreTemplate::PIN));
+        assert!(!validate_password("12a4", SpectreTemplate::PIN));
+        assert!(!validate_password("12345", SpectreTemplate::PIN));
+    }
+}
@@ -126,7 +126,7 @@ fn test_raises_an_error_for_invalid_length_md5() {
 
     let result = generate_password("https://example.com", "master", &config);
     assert!(result.is_err());
-    assert!(matches!(result, Err(InvalidLengthError)));
+    assert!(matches!(result, Err(GenerationError::InvalidLength)));
 }
 
 #[test]
@@ -138,5 +138,124 @@ fn test_raises_an_error_for_invalid_length_sha512() {
 
     let result = generate_password("https://example.com", "master", &config);
     assert!(result.is_err());
-    assert!(matches!(result, Err(InvalidLengthError)));
-}
\ No newline at end of file
+    assert!(matches!(result, Err(GenerationError::InvalidLength)));
+}
+
+#[test]
+fn test_apply_password_hops_single_hop_does_not_underflow() {
+    let config = Config::default().with_hops(1);
+
+    let out = apply_password_hops("my_master_password:example.com", &config).unwrap();
+    assert_eq!(out.len(), config.length as usize);
+    assert!(validate_password(&out, &config));
+}
+
+#[test]
+fn test_apply_password_hops_single_hop_rehashes_until_valid() {
+    let config = Config::KGPG.with_hops(1);
+    let first_hop = |input: &str| apply_kgpg(&hash_sha512(input));
+    // Find an input whose only hop fails validation, so the last hop has to be hashed again
+    let input = (0..).map(|n| format!("my_master_password:site{}.com", n))
+        .find(|input| !validate_password(&first_hop(input), &config))
+        .unwrap();
+
+    let mut expected = first_hop(&input);
+    while !validate_password(&expected, &config) {
+        expected = first_hop(&expected);
+    }
+    expected.truncate(config.length as usize);
+    assert_eq!(apply_password_hops(&input, &config).unwrap(), expected);
+}
+
+#[test]
+fn test_derive_bytes_is_deterministic_and_separated_by_label() {
+    let first = derive_bytes("username", "master", "example.com");
+    assert_eq!(first, derive_bytes("username", " master ", "example.com"));
+    assert_ne!(first, derive_bytes("username", "master", "test.com"));
+    assert_ne!(first, derive_bytes("totp", "master", "example.com"));
+}
+
+#[test]
+fn test_generate_password_is_the_same_for_equivalent_hosts() {
+    let config = Config::KGPG;
+    let expected = generate_password("https://xn--bcher-kva.de", "master", &config).unwrap();
+    for spelling in ["https://bücher.de", "https://BÜCHER.DE./", "bücher.de", "BÜCHER.DE", "Bücher.de."] {
+        assert_eq!(generate_password(spelling, "master", &config).unwrap(), expected, "{}", spelling);
+    }
+}
+
+#[test]
+fn test_validate_password_report_lists_failed_rules() {
+    let config = Config::KGPG;
+    assert!(validate_password_report("bAcdef1!asdfgasd", &config).is_valid());
+    assert_eq!(validate_password_report("bAcdefg!asdfgas", &config).failed(), vec![Rule::ContainsDigit]);
+    assert_eq!(validate_password_report("bAcdef1!asdfga~", &config).failed(), vec![Rule::AllowedCharacters]);
+    assert_eq!(validate_password_report("bacdef1!asdfgas", &config).failed(), vec![Rule::ContainsUppercase]);
+
+    let report = validate_password_report("short", &Config::SGP);
+    assert_eq!(report.rules.len(), 5);
+    assert!(report.failed().contains(&Rule::Length));
+
+    let short_config = Config::SGP.with_length(6);
+    assert_eq!(validate_password_report("bcAd12", &short_config).failed(), vec![Rule::Length]);
+}
+
+#[test]
+fn test_validate_password_report_for_other_generators() {
+    let lesspass = validate_password_report("abcdefghijklmnop", &Config::LESSPASS);
+    assert_eq!(lesspass.failed(), vec![Rule::CharacterSets]);
+    assert_eq!(validate_password_report("abc", &Config::LESSPASS).failed(), vec![Rule::Length, Rule::CharacterSets]);
+
+    assert_eq!(validate_password_report("1234", &Config::SPECTRE).failed(), vec![Rule::Template]);
+    assert!(validate_password_report("12345678", &Config::PASSWORDMAKER).is_valid());
+}
+
+#[test]
+fn test_entropy_bits_depends_on_alphabet_and_length() {
+    assert!((entropy_bits(&Config::KGPG) - (26f64.log2() + 14.0 * 6.0)).abs() < 1e-9);
+    assert!(entropy_bits(&Config::SGP) < entropy_bits(&Config::SGP.with_generator_type(GeneratorType::KGPG)));
+    assert!(entropy_bits(&Config::KGPG.with_length(8)) < RECOMMENDED_ENTROPY_BITS);
+    assert_eq!(entropy_bits(&Config::KGPG.with_length(84)), 26f64.log2() + 83.0 * 6.0);
+
+    assert!((entropy_bits(&Config::LESSPASS) - 16.0 * 94f64.log2()).abs() < 1e-9);
+    assert!((entropy_bits(&Config::PASSWORDMAKER) - 8.0 * 94f64.log2()).abs() < 1e-9);
+    let pin = crate::config::SpectreProfile::default().with_template(crate::config::SpectreTemplate::PIN);
+    let pin_config = Config::SPECTRE.with_generator_type(GeneratorType::Spectre(pin));
+    assert!((entropy_bits(&pin_config) - 10_000f64.log2()).abs() < 1e-9);
+
+    let syllables = 6.0 * (4.0 + modulo_entropy_bits(64, 5)) + 4.0;
+    assert!((entropy_bits(&Config::PRONOUNCEABLE) - (syllables + 2.0 * modulo_entropy_bits(64, 10) + modulo_entropy_bits(64, 6))).abs() < 1e-9);
+}
+
+#[test]
+fn test_pronounceable_passwords_pass_their_rules() {
+    let config = Config::PRONOUNCEABLE;
+    let password = generate_password("https://example.com", "my_master_password", &config).unwrap();
+    assert_eq!(password.len(), config.length as usize);
+    assert!(validate_password_report(&password, &config).is_valid());
+    assert_eq!(password, generate_password("https://www.example.com", "my_master_password", &config).unwrap());
+    assert_ne!(password, generate_password("https://example.org", "my_master_password", &config).unwrap());
+
+    assert_eq!(validate_password_report("kabotemiruzab12!", &config).failed(), vec![Rule::Syllables, Rule::ContainsUppercase]);
+    let short = crate::config::PronounceableProfile::default().with_digits(15);
+    assert!(generate_password("https://example.com", "my_master_password", &config.with_generator_type(GeneratorType::Pronounceable(short))).is_err());
+}
+
+
+#[test]
+fn test_templates_fill_hop_based_passwords() {
+    let template = crate::template::Template::parse("A{4}-9{4}-a{4}s").unwrap();
+    let config = Config::KGPG.with_template(template.clone());
+    let password = generate_password("https://example.com", "my_master_password", &config).unwrap();
+    assert!(template.matches(&password));
+    assert_eq!(password, generate_password("https://example.com", "my_master_password", &Config::SGP.with_hops(15).with_template(template.clone())).unwrap());
+    assert_ne!(password, generate_password("https://example.org", "my_master_password", &config).unwrap());
+    assert_eq!(validate_password_report("AAAA-1234-aaa", &config).failed(), vec![Rule::Template]);
+    assert!((entropy_bits(&config) - template.entropy_bits()).abs() < 1e-9);
+
+    let md5 = Config::KGPG.with_hash_algorithm(HashAlgorithm::MD5);
+    assert!(generate_password("https://example.com", "my_master_password", &md5.with_template("a{16}".parse().unwrap())).is_ok());
+    let result = generate_password("https://example.com", "my_master_password", &Config::KGPG.with_hash_algorithm(HashAlgorithm::MD5).with_template("a{17}".parse().unwrap()));
+    assert!(matches!(result, Err(GenerationError::Template(_))));
+    assert_eq!(generate_password("https://example.com", "password", &Config::LESSPASS.with_template(template)).unwrap().len(), 16);
+}
@@ -0,0 +1,241 @@
+//! Imports of site lists from browser and password manager exports, to bootstrap the profile store.
+//! Chrome and Firefox password CSV exports and unencrypted Bitwarden JSON exports are read.
+//! Logins are keyed by the host from `url_helper::get_host` and deduplicated by host.
+//! The stored passwords are only used to report which presets already generate them, they are never saved.
+//!
+//! # Examples
+//! ```
+//! use kg_passgen::import::{add_to_store, dedupe_by_host, read_browser_csv};
+//! use kg_passgen::profile::ProfileStore;
+//! let csv = "name,url,username,password\nexample,https://www.example.com/login,me,hunter2\nexample,https://example.com,me,hunter2\n";
+//!
+//! let logins = dedupe_by_host(read_browser_csv(csv, &true).unwrap());
+//! assert_eq!(logins.len(), 1);
+//! assert_eq!(logins[0].host, "example.com");
+//!
+//! let store = add_to_store(ProfileStore::default(), &logins, &true);
+//! assert_eq!(store.sites[0].url, "https://www.example.com/login");
+//! assert_eq!(store.sites[0].username, "me");
+//! ```
+
+use core::fmt;
+use std::collections::HashSet;
+
+use crate::config::{Config, GeneratorType};
+use crate::export::bitwarden::{BitwardenExport, LOGIN_TYPE};
+use crate::generator::generate_password;
+use crate::profile::{ProfileStore, SiteProfile};
+use crate::url_helper::get_host;
+
+/// The presets checked against stored passwords.
+/// Spectre is left out, its passwords depend on the full name of the user, which exports do not record.
+pub const PRESETS: [(&str, Config); 5] = [
+    ("KGPG", Config::KGPG),
+    ("SGP", Config::SGP),
+    ("LessPass", Config::LESSPASS),
+    ("PasswordMaker", Config::PASSWORDMAKER),
+    ("Pronounceable", Config::PRONOUNCEABLE),
+];
+
+/// Custom error type for exports that could not be read
+#[derive(Debug, Clone)]
+pub struct ImportError(pub String);
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to import: {}", self.0)
+    }
+}
+
+/// A login read from an export
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ImportedLogin {
+    /// The host of the URL, from `get_host`
+    pub host: String,
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    pub group: String,
+}
+
+impl ImportedLogin {
+    fn new(url: &str, username: &str, password: &str, group: &str, strip_subdomain: &bool) -> Self {
+        ImportedLogin {
+            host: get_host(url.trim(), strip_subdomain),
+            url: url.trim().to_string(),
+            username: username.trim().to_string(),
+            password: password.to_string(),
+            group: group.trim().to_string(),
+        }
+    }
+}
+
+/// Splits CSV contents into rows of fields, quoted fields may hold commas, quotes and line breaks
+fn parse_csv(contents: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = contents.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            },
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => row.push(std::mem::take(&mut field)),
+            ('\r', false) if chars.peek() == Some(&'\n') => {},
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            },
+            (c, _) => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows.retain(|row| row.iter().any(|field| !field.is_empty()));
+    rows
+}
+
+/// Reads a Chrome or Firefox password CSV export, columns are found by the `url`, `username` and `password` headers.
+/// Rows without a URL are skipped.
+pub fn read_browser_csv(contents: &str, strip_subdomain: &bool) -> Result<Vec<ImportedLogin>, ImportError> {
+    let mut rows = parse_csv(contents).into_iter();
+    let header = rows.next().unwrap_or_default();
+    let column = |name: &str| header.iter().position(|field| field.trim().eq_ignore_ascii_case(name));
+    let Some(url) = column("url") else {
+        return Err(ImportError("the CSV file has no url column".to_string()));
+    };
+    let (username, password) = (column("username"), column("password"));
+    let field = |row: &[String], index: Option<usize>| index.and_then(|index| row.get(index)).cloned().unwrap_or_default();
+
+    Ok(rows
+        .filter(|row| !field(row, Some(url)).trim().is_empty())
+        .map(|row| ImportedLogin::new(&field(&row, Some(url)), &field(&row, username), &field(&row, password), "", strip_subdomain))
+        .collect())
+}
+
+/// Reads an unencrypted Bitwarden JSON export, every URI of a login is read and folders become groups
+pub fn read_bitwarden_json(contents: &str, strip_subdomain: &bool) -> Result<Vec<ImportedLogin>, ImportError> {
+    let export = BitwardenExport::from_json(contents).map_err(|e| ImportError(e.to_string()))?;
+    if export.encrypted {
+        return Err(ImportError("encrypted Bitwarden exports are not supported".to_string()));
+    }
+
+    let mut logins = Vec::new();
+    for item in export.items.iter().filter(|item| item.item_type == LOGIN_TYPE) {
+        let Some(login) = &item.login else { continue };
+        let group = export.folders.iter().find(|folder| Some(&folder.id) == item.folder_id.as_ref()).map(|folder| folder.name.as_str()).unwrap_or_default();
+        for uri in login.uris.iter().filter_map(|uri| uri.uri.as_deref()).filter(|uri| !uri.trim().is_empty()) {
+            let username = login.username.as_deref().unwrap_or_default();
+            let password = login.password.as_deref().unwrap_or_default();
+            logins.push(ImportedLogin::new(uri, username, password, group, strip_subdomain));
+        }
+    }
+    Ok(logins)
+}
+
+/// Keeps the first login of every host
+pub fn dedupe_by_host(logins: Vec<ImportedLogin>) -> Vec<ImportedLogin> {
+    let mut hosts = HashSet::new();
+    logins.into_iter().filter(|login| !login.host.is_empty() && hosts.insert(login.host.clone())).collect()
+}
+
+/// Adds a site for every login whose host is not in the store yet.
+/// Sites keep the URL of the login, so exports derive their host with their own config.
+pub fn add_to_store(store: ProfileStore, logins: &[ImportedLogin], strip_subdomain: &bool) -> ProfileStore {
+    let mut hosts: HashSet<String> = store.sites.iter().map(|site| get_host(&site.url, strip_subdomain)).collect();
+    logins.iter().filter(|login| hosts.insert(login.host.clone())).fold(store, |store, login| {
+        store.with_site(SiteProfile::new(&login.url).with_username(&login.username).with_group(&login.group))
+    })
+}
+
+/// Returns the names of the presets generating the stored password of the login.
+/// LessPass is checked with the username of the login as its login.
+/// # Examples
+/// ```
+/// use kg_passgen::import::{matching_presets, ImportedLogin};
+/// let login = ImportedLogin { url: "https://example.com".to_string(), password: "mXApUt1OgTb$xZh".to_string(), ..Default::default() };
+/// assert_eq!(matching_presets(&login, "my_master_password"), vec!["KGPG"]);
+/// ```
+pub fn matching_presets(login: &ImportedLogin, master_password: &str) -> Vec<&'static str> {
+    if login.password.is_empty() {
+        return Vec::new();
+    }
+    PRESETS.iter()
+        .filter(|(_, config)| {
+            let password = match &config.generator_type {
+                GeneratorType::LessPass(profile) => {
+                    let profile = profile.clone().with_login(&login.username);
+                    generate_password(&login.url, master_password, &Config::LESSPASS.with_generator_type(GeneratorType::LessPass(profile)))
+                },
+                _ => generate_password(&login.url, master_password, config),
+            };
+            password.is_ok_and(|password| password == login.password)
+        })
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_browser_csv_handles_quotes_and_column_order() {
+        let firefox = "\u{feff}\"url\",\"username\",\"password\",\"httpRealm\"\r\n\"https://mail.example.com\",\"me\",\"a,\"\"b\"\"\nc\",\r\n\"\",\"nobody\",\"x\",\r\n";
+        let logins = read_browser_csv(firefox, &true).unwrap();
+        assert_eq!(logins, vec![ImportedLogin {
+            host: "example.com".to_string(),
+            url: "https://mail.example.com".to_string(),
+            username: "me".to_string(),
+            password: "a,\"b\"\nc".to_string(),
+            group: String::new(),
+        }]);
+        assert_eq!(read_browser_csv(firefox, &false).unwrap()[0].host, "mail.example.com");
+        assert!(read_browser_csv("name,username\n", &true).is_err());
+    }
+
+    #[test]
+    fn test_read_bitwarden_json_reads_every_uri() {
+        let json = r#"{"encrypted": false, "folders": [{"id": "f1", "name": "Work"}], "items": [
+            {"id": "1", "folderId": "f1", "type": 1, "name": "Example", "login": {"uris": [{"match": null, "uri": "https://example.com"}, {"match": null, "uri": "androidapp://com.example.app"}], "username": "me", "password": "secret"}},
+            {"id": "2", "type": 2, "name": "Note"},
+            {"id": "3", "type": 1, "name": "No URI", "login": {"uris": [], "username": "me"}}
+        ]}"#;
+        let logins = read_bitwarden_json(json, &true).unwrap();
+        assert_eq!(logins.iter().map(|login| login.host.as_str()).collect::<Vec<_>>(), vec!["example.com", "example.com"]);
+        assert!(logins.iter().all(|login| login.group == "Work" && login.password == "secret"));
+        assert_eq!(dedupe_by_host(logins).len(), 1);
+        assert!(read_bitwarden_json(r#"{"encrypted": true}"#, &true).is_err());
+    }
+
+    #[test]
+    fn test_matching_presets_uses_the_username_as_lesspass_login() {
+        let lesspass = Config::LESSPASS.with_generator_type(GeneratorType::LessPass(crate::config::LessPassProfile::default().with_login("me@example.com")));
+        let password = generate_password("https://example.com", "my_master_password", &lesspass).unwrap();
+        let login = ImportedLogin::new("https://example.com", "me@example.com", &password, "", &true);
+        assert_eq!(matching_presets(&login, "my_master_password"), vec!["LessPass"]);
+        assert!(matching_presets(&ImportedLogin { username: String::new(), ..login }, "my_master_password").is_empty());
+    }
+
+    #[test]
+    fn test_add_to_store_skips_known_hosts() {
+        let store = ProfileStore::default().with_site(SiteProfile::new("https://www.example.com"));
+        let logins = vec![
+            ImportedLogin::new("https://example.com/login", "", "", "", &true),
+            ImportedLogin::new("https://mail.com:8443/inbox", "me", "", "", &true),
+        ];
+        let store = add_to_store(store, &logins, &true);
+        assert_eq!(store.sites, vec![SiteProfile::new("https://www.example.com"), SiteProfile::new("https://mail.com:8443/inbox").with_username("me")]);
+
+        let kept = add_to_store(ProfileStore::default().with_site(SiteProfile::new("https://www.example.com")), &[ImportedLogin::new("https://example.com", "", "", "", &false)], &false);
+        assert_eq!(kept.sites.len(), 2);
+    }
+}
@@ -1,3 +1,15 @@
+pub mod answer;
+pub mod breach;
 pub mod config;
+pub mod encryption;
+pub mod export;
 pub mod generator;
-pub mod url_helper;
\ No newline at end of file
+pub mod import;
+pub mod keys;
+pub mod otp;
+pub mod profile;
+pub mod strength;
+pub mod suffix_list;
+pub mod template;
+pub mod url_helper;
+pub mod username;
\ No newline at end of file
@@ -1,8 +1,62 @@
-use std::io;
+use std::io::{self, BufRead};
 use arboard::Clipboard;
-use inquire::{Confirm, CustomType, Password, Select, Text, required, validator::Validation};
+use inquire::{Confirm, CustomType, MultiSelect, Password, Select, Text, required, validator::Validation};
+use kg_passgen::config::{Config, GeneratorType};
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some("host") = args.first().map(String::as_str) {
+        host_command(&args[1..]);
+        return;
+    }
+
+    if let Some("build-breach-filter") = args.first().map(String::as_str) {
+        build_breach_filter_command(&args[1..]);
+        return;
+    }
+
+    if let Some("export") = args.first().map(String::as_str) {
+        export_command(&args[1..]);
+        return;
+    }
+
+    if let Some("import") = args.first().map(String::as_str) {
+        import_command(&args[1..]);
+        return;
+    }
+
+    if let Some("keys") = args.first().map(String::as_str) {
+        keys_command(&args[1..]);
+        return;
+    }
+
+    if let Some("totp") = args.first().map(String::as_str) {
+        totp_command(&args[1..]);
+        return;
+    }
+
+    if let Some(command @ ("encrypt" | "decrypt")) = args.first().map(String::as_str) {
+        age_command(command == "encrypt", &args[1..]);
+        return;
+    }
+
+    let options = match parse_options(&args) {
+        Ok(options) => options,
+        Err(message) => {
+            println!("{}", message);
+            println!("Usage: kg_passgen [--min-score <0-4>] [--breach-dump <file> | --breach-filter <file>]");
+            println!("       kg_passgen host <url>");
+            println!("       kg_passgen build-breach-filter <dump> <filter>");
+            println!("       {}", EXPORT_USAGE);
+            println!("       {}", IMPORT_USAGE);
+            println!("       kg_passgen <encrypt|decrypt> <input> <output>");
+            println!("       kg_passgen totp <url> [--enroll]");
+            println!("       kg_passgen keys <label> <directory>");
+            return;
+        }
+    };
+    let min_score = options.min_score;
+
     println!("KG Password Generator");
     let mut clipboard = match Clipboard::new() {
         Ok(clipboard) => clipboard,
@@ -12,32 +66,793 @@ fn main() {
         }
     };
 
+    let mode = Select::new("What do you want to generate?", vec!["Password", "Username", "Security answer"])
+        .with_help_message("Choose what to derive from your master password and the service URL")
+        .prompt()
+        .expect("Failed to read input");
+
+    let master_password = prompt_master_password(min_score);
+    if let Some(breach) = &options.breach {
+        report_breach(breach, "master password", &master_password);
+    }
+
+    let url = Text::new("Service URL:")
+        .with_placeholder("e.g., https://example.com")
+        .with_help_message("The website or service you are trying to generate a password for")
+        .prompt()
+        .expect("Failed to read input");
+
+
+    match mode {
+        "Username" => username_mode(&mut clipboard, &url, &master_password),
+        "Security answer" => answer_mode(&mut clipboard, &url, &master_password),
+        _ => password_mode(&mut clipboard, &url, &master_password, options.breach.as_ref()),
+    }
+
+    println!("Press Enter to exit...");
+    io::stdin().read_line(&mut String::new()).expect("Failed to detect input. Exiting");
+}
+
+/// Prompts for the master password, refusing passwords scoring below `min_score` and warning below the recommended score
+fn prompt_master_password(min_score: u8) -> String {
     let master_password = Password::new("Master Password:")
         .with_help_message("Your master password used to derive service passwords")
         .with_display_mode(inquire::PasswordDisplayMode::Masked)
         .with_validator(required!("A master password is required"))
+        .with_validator(move |input: &str| {
+            let estimate = kg_passgen::strength::estimate_strength(input);
+            if estimate.score < min_score {
+                Ok(Validation::Invalid(format!("Master password is too weak (score {} of 4, at least {} required). {}", estimate.score, min_score, estimate.feedback.join(" ")).into()))
+            } else {
+                Ok(Validation::Valid)
+            }
+        })
         .without_confirmation()
         .prompt()
         .expect("Failed to read input");
 
-    let url = Text::new("Service URL:")
-        .with_placeholder("e.g., https://example.com")
-        .with_help_message("The website or service you are trying to generate a password for")
+    let estimate = kg_passgen::strength::estimate_strength(&master_password);
+    if estimate.score < kg_passgen::strength::RECOMMENDED_SCORE {
+        println!("Warning: weak master password (score {} of 4). {}", estimate.score, estimate.feedback.join(" "));
+    }
+    master_password
+}
+
+/// Options of the interactive mode
+struct Options {
+    /// Master passwords scoring lower are refused
+    min_score: u8,
+    breach: Option<kg_passgen::breach::BreachSource>,
+}
+
+/// Reads `--min-score <0-4>` (defaults to 0) and `--breach-dump <file>` or `--breach-filter <file>`
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options { min_score: 0, breach: None };
+    for pair in args.chunks(2) {
+        match pair {
+            [flag, value] if flag == "--min-score" => match value.parse::<u8>() {
+                Ok(score) if score <= 4 => options.min_score = score,
+                _ => return Err(format!("Invalid minimum score: {}", value)),
+            },
+            [flag, value] if flag == "--breach-dump" => options.breach = Some(kg_passgen::breach::BreachSource::Dump(value.into())),
+            [flag, value] if flag == "--breach-filter" => match kg_passgen::breach::BreachSource::load_filter(value) {
+                Ok(source) => options.breach = Some(source),
+                Err(e) => return Err(format!("Failed to read the breach filter: {}", e)),
+            },
+            _ => return Err(format!("Unknown arguments: {}", pair.join(" "))),
+        }
+    }
+    Ok(options)
+}
+
+/// `kg_passgen build-breach-filter <dump> <filter>` builds a filter with a 0.1% false positive rate
+fn build_breach_filter_command(args: &[String]) {
+    let [dump, filter] = args else {
+        println!("Usage: kg_passgen build-breach-filter <dump> <filter>");
+        return;
+    };
+
+    let build = || -> io::Result<()> {
+        let items = io::BufReader::new(std::fs::File::open(dump)?).lines().count() as u64;
+        let bloom = kg_passgen::breach::BloomFilter::from_dump(io::BufReader::new(std::fs::File::open(dump)?), items, 0.001)?;
+        bloom.write_to(&mut io::BufWriter::new(std::fs::File::create(filter)?))?;
+        println!("Wrote a filter of {} hashes to {}", items, filter);
+        Ok(())
+    };
+    if let Err(e) = build() {
+        println!("Failed to build the breach filter: {}.", e);
+    }
+}
+
+const EXPORT_USAGE: &str = "kg_passgen export <keepass|kdbx|bitwarden|1password|pass> <sites> <output> [--age]";
+
+/// `kg_passgen export <format> <sites> <output>` derives the password of every site of a site list or profile store
+/// and writes them for another password manager
+fn export_command(args: &[String]) {
+    let (format, sites, output, encrypt) = match args {
+        [format, sites, output] => (format, sites, output, false),
+        [format, sites, output, flag] if flag == "--age" && format != "pass" => (format, sites, output, true),
+        _ => {
+            println!("Usage: {}", EXPORT_USAGE);
+            return;
+        }
+    };
+    if !["keepass", "kdbx", "bitwarden", "1password", "pass"].contains(&format.as_str()) {
+        println!("Unknown export format: {}", format);
+        println!("Usage: {}", EXPORT_USAGE);
+        return;
+    }
+
+    let master_password = prompt_master_password(0);
+    let store = match is_age_file(sites) {
+        true => kg_passgen::profile::ProfileStore::load_encrypted(sites, &prompt_age_key(false, Some(&master_password))),
+        false => kg_passgen::profile::ProfileStore::load(sites),
+    };
+    let store = match store {
+        Ok(store) => store,
+        Err(e) => {
+            println!("Failed to read the sites: {}.", e);
+            return;
+        }
+    };
+    let config = prompt_equivalences(prompt_config()).with_suffix_list_version(&store.suffix_list_version);
+    if let Err(e) = kg_passgen::suffix_list::check_suffix_list(&config) {
+        println!("Warning: {}.", e);
+    }
+    let entries = match kg_passgen::export::export_entries(&store, &master_password, &config) {
+        Ok(entries) => entries,
+        Err(e) => {
+            println!("Error generating password: {}.", e);
+            return;
+        }
+    };
+
+    if format == "pass" {
+        let encryptor = prompt_pass_encryptor(output);
+        match kg_passgen::export::pass::write_store(output, &entries, &encryptor) {
+            Ok(paths) => println!("Exported {} passwords to {}", paths.len(), output),
+            Err(e) => println!("Failed to write the password store: {}.", e),
+        }
+        return;
+    }
+
+    let contents = match format.as_str() {
+        "kdbx" => {
+            let database_password = Password::new("Database Password:")
+                .with_help_message("The password protecting the KeePass database")
+                .with_display_mode(inquire::PasswordDisplayMode::Masked)
+                .with_validator(required!("A database password is required"))
+                .with_custom_confirmation_message("Confirm Database Password:")
+                .prompt()
+                .expect("Failed to read input");
+            match kg_passgen::export::keepass::to_kdbx4(&entries, &database_password, &kg_passgen::export::keepass::Kdbx4Options::DEFAULT) {
+                Ok(database) => database,
+                Err(e) => {
+                    println!("{}.", e);
+                    return;
+                }
+            }
+        },
+        "bitwarden" => kg_passgen::export::bitwarden::to_json(&entries).into_bytes(),
+        "1password" => kg_passgen::export::onepassword::to_csv(&entries).into_bytes(),
+        _ => kg_passgen::export::keepass::to_xml(&entries).into_bytes(),
+    };
+    let contents = match encrypt {
+        true => match kg_passgen::encryption::encrypt(&contents, &prompt_age_key(true, Some(&master_password))) {
+            Ok(encrypted) => encrypted,
+            Err(e) => {
+                println!("Failed to encrypt the export: {}.", e);
+                return;
+            }
+        },
+        false => contents,
+    };
+    // Exports without --age hold every password in plain text
+    let written = match encrypt {
+        true => std::fs::write(output, contents),
+        false => write_private_file(std::path::Path::new(output), &contents),
+    };
+    match written {
+        Ok(_) => println!("Exported {} passwords to {}", entries.len(), output),
+        Err(e) => println!("Failed to write the export: {}.", e),
+    }
+}
+
+/// Prompts for the command encrypting the files of a password store,
+/// GPG uses the recipients of the `.gpg-id` file of the store when it has one
+fn prompt_pass_encryptor(directory: &str) -> kg_passgen::export::pass::PassEncryptor {
+    let encryption = Select::new("Select Encryption", vec!["GPG", "age", "Custom command"])
+        .with_help_message("Choose the command encrypting every file of the password store")
         .prompt()
         .expect("Failed to read input");
 
-    let kg_config = format!("KGPG {:?}", kg_passgen::config::Config::KGPG);
-    let sgp_config = format!("SGP {:?}", kg_passgen::config::Config::SGP);
-    let select_config = Select::new("Select Configuration", vec![&kg_config, &sgp_config, "Custom"])
+    if encryption == "Custom command" {
+        let command = Text::new("Encryption Command:")
+            .with_placeholder("e.g., gpg --encrypt --recipient me@mail.com")
+            .with_help_message("Reads the plaintext from stdin and writes the ciphertext to stdout, quote arguments with spaces")
+            .with_validator(|command: &str| match kg_passgen::export::pass::PassEncryptor::from_command(command, "") {
+                Some(_) => Ok(Validation::Valid),
+                None => Ok(Validation::Invalid("A command without unclosed quotes is required".into())),
+            })
+            .prompt()
+            .expect("Failed to read input");
+        let extension = Text::new("File Extension:")
+            .with_default("gpg")
+            .prompt()
+            .expect("Failed to read input");
+        return kg_passgen::export::pass::PassEncryptor::from_command(&command, extension.trim_start_matches('.'))
+            .expect("The command was validated");
+    }
+
+    if encryption == "GPG" && let Ok(encryptor) = kg_passgen::export::pass::PassEncryptor::gpg_for_store(directory) {
+        println!("Encrypting for the recipients of {}/.gpg-id", directory);
+        return encryptor;
+    }
+    let recipients = Text::new("Recipients:")
+        .with_help_message("GPG key ids or age public keys, separated by spaces")
+        .with_validator(required!("At least one recipient is required"))
+        .prompt()
+        .expect("Failed to read input");
+    let recipients: Vec<&str> = recipients.split_whitespace().collect();
+    match encryption {
+        "age" => kg_passgen::export::pass::PassEncryptor::age(&recipients),
+        _ => kg_passgen::export::pass::PassEncryptor::gpg(&recipients),
+    }
+}
+
+const IMPORT_USAGE: &str = "kg_passgen import <csv|bitwarden> <export> <store> [--check] [--keep-subdomains]";
+
+/// `kg_passgen import <format> <export> <store> [--check] [--keep-subdomains]` adds the sites of a browser CSV or Bitwarden JSON export
+/// to the profile store, and with `--check` reports which presets generate the stored passwords.
+/// Hosts are deduplicated without their subdomains unless `--keep-subdomains` is given.
+fn import_command(args: &[String]) {
+    let (format, export, store_path, flags) = match args {
+        [format, export, store, flags @ ..] if flags.iter().all(|flag| flag == "--check" || flag == "--keep-subdomains") => (format, export, store, flags),
+        _ => {
+            println!("Usage: {}", IMPORT_USAGE);
+            return;
+        }
+    };
+    let check = flags.iter().any(|flag| flag == "--check");
+    let strip_subdomain = !flags.iter().any(|flag| flag == "--keep-subdomains");
+
+    let contents = match std::fs::read_to_string(export) {
+        Ok(contents) => contents,
+        Err(e) => {
+            println!("Failed to read the export: {}.", e);
+            return;
+        }
+    };
+    let logins = match format.as_str() {
+        "csv" => kg_passgen::import::read_browser_csv(&contents, &strip_subdomain),
+        "bitwarden" => kg_passgen::import::read_bitwarden_json(&contents, &strip_subdomain),
+        _ => {
+            println!("Unknown import format: {}", format);
+            println!("Usage: {}", IMPORT_USAGE);
+            return;
+        }
+    };
+    let logins = match logins {
+        Ok(logins) => kg_passgen::import::dedupe_by_host(logins),
+        Err(e) => {
+            println!("{}.", e);
+            return;
+        }
+    };
+
+    let exists = std::path::Path::new(store_path).exists();
+    let key = match exists {
+        true => is_age_file(store_path).then(|| prompt_age_key(false, None)),
+        false => store_path.ends_with(".age").then(|| prompt_age_key(true, None)),
+    };
+    let store = match (exists, &key) {
+        (true, Some(key)) => kg_passgen::profile::ProfileStore::load_encrypted(store_path, key),
+        (true, None) => kg_passgen::profile::ProfileStore::load(store_path),
+        // New stores record the list their passwords will be generated with, `export` compares it
+        (false, _) => Ok(kg_passgen::profile::ProfileStore::default().with_suffix_list_version(&prompt_suffix_list().version())),
+    };
+    let store = match store {
+        Ok(store) => store,
+        Err(e) => {
+            println!("Failed to read the profile store: {}.", e);
+            return;
+        }
+    };
+    let known = store.sites.len();
+    let store = kg_passgen::import::add_to_store(store, &logins, &strip_subdomain);
+    let saved = match &key {
+        Some(key) => store.save_encrypted(store_path, key),
+        None => store.save(store_path),
+    };
+    match saved {
+        Ok(_) => println!("Added {} of {} imported hosts to {}", store.sites.len() - known, logins.len(), store_path),
+        Err(e) => println!("Failed to write the profile store: {}.", e),
+    }
+
+    if check {
+        let master_password = prompt_master_password(0);
+        for login in &logins {
+            match kg_passgen::import::matching_presets(login, &master_password)[..] {
+                [] => println!("{}: no preset generates the stored password", login.host),
+                ref presets => println!("{}: matches {}", login.host, presets.join(", ")),
+            }
+        }
+    }
+}
+
+/// Checks whether the file is encrypted with age
+fn is_age_file(path: &str) -> bool {
+    std::fs::read(path).is_ok_and(|data| kg_passgen::encryption::is_encrypted(&data))
+}
+
+/// Prompts for the age key of a file, the master password is asked for when the derived identity is chosen without it
+fn prompt_age_key(encrypting: bool, master_password: Option<&str>) -> kg_passgen::encryption::AgeKey {
+    let external = if encrypting { "Recipient" } else { "Identity file" };
+    let mode = Select::new("Select age Key", vec!["Derived from the master password", "Passphrase", external])
+        .with_help_message("The derived key needs no key file, only your master password")
+        .prompt()
+        .expect("Failed to read input");
+
+    match mode {
+        "Passphrase" => {
+            let mut prompt = Password::new("age Passphrase:")
+                .with_display_mode(inquire::PasswordDisplayMode::Masked)
+                .with_validator(required!("A passphrase is required"));
+            if !encrypting {
+                prompt = prompt.without_confirmation();
+            }
+            kg_passgen::encryption::AgeKey::Passphrase(prompt.prompt().expect("Failed to read input"))
+        },
+        "Recipient" => {
+            let recipient = Text::new("age Recipient:")
+                .with_placeholder("age1...")
+                .with_validator(required!("A recipient is required"))
+                .prompt()
+                .expect("Failed to read input");
+            kg_passgen::encryption::AgeKey::Recipient(recipient)
+        },
+        "Identity file" => {
+            let path = Text::new("age Identity File:")
+                .with_validator(required!("An identity file is required"))
+                .prompt()
+                .expect("Failed to read input");
+            let identity = std::fs::read_to_string(path.trim()).unwrap_or_default();
+            let identity = identity.lines().map(str::trim).find(|line| line.starts_with("AGE-SECRET-KEY-")).unwrap_or_default();
+            kg_passgen::encryption::AgeKey::Identity(identity.to_string())
+        },
+        _ => match master_password {
+            Some(master_password) => kg_passgen::encryption::AgeKey::derived(master_password),
+            None => kg_passgen::encryption::AgeKey::derived(&prompt_master_password(0)),
+        },
+    }
+}
+
+/// `kg_passgen encrypt <input> <output>` and `kg_passgen decrypt <input> <output>` encrypt any file, such as a profile store or export, with age
+fn age_command(encrypting: bool, args: &[String]) {
+    let [input, output] = args else {
+        println!("Usage: kg_passgen {} <input> <output>", if encrypting { "encrypt" } else { "decrypt" });
+        return;
+    };
+    let data = match std::fs::read(input) {
+        Ok(data) => data,
+        Err(e) => {
+            println!("Failed to read {}: {}.", input, e);
+            return;
+        }
+    };
+
+    let key = prompt_age_key(encrypting, None);
+    let result = match encrypting {
+        true => kg_passgen::encryption::encrypt(&data, &key),
+        false => kg_passgen::encryption::decrypt(&data, &key),
+    };
+    // Decrypted files are written like plain text exports
+    let write = |data: Vec<u8>| match encrypting {
+        true => std::fs::write(output, data),
+        false => write_private_file(std::path::Path::new(output), &data),
+    };
+    match result.map(write) {
+        Ok(Ok(_)) => println!("Wrote {}", output),
+        Ok(Err(e)) => println!("Failed to write {}: {}.", output, e),
+        Err(e) => println!("{}.", e),
+    }
+}
+
+/// Writes a file only the owner can read
+fn write_private_file(path: &std::path::Path, contents: &[u8]) -> io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    // The mode only applies to new files, existing ones are tightened before writing
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    io::Write::write_all(&mut file, contents)
+}
+
+/// `kg_passgen keys <label> <directory>` writes the SSH keypair and age identity derived for the label
+fn keys_command(args: &[String]) {
+    let [label, directory] = args else {
+        println!("Usage: kg_passgen keys <label> <directory>");
+        return;
+    };
+
+    let master_password = prompt_master_password(0);
+    let ssh_key = kg_passgen::keys::derive_ssh_key(&master_password, label);
+    let age_identity = kg_passgen::keys::derive_age_identity(&master_password, label);
+    let kg_passgen::encryption::AgeKey::Identity(identity) = &age_identity else {
+        println!("Failed to derive the age identity, no keys were written.");
+        return;
+    };
+    let recipient = age_identity.recipient().unwrap_or_default();
+
+    let name = kg_passgen::url_helper::normalize_host(label).replace(['/', '\\', ' '], "_");
+    let directory = std::path::Path::new(directory);
+    let write = || -> io::Result<()> {
+        std::fs::create_dir_all(directory)?;
+        write_private_file(&directory.join(format!("id_ed25519_{}", name)), ssh_key.private_key.as_bytes())?;
+        std::fs::write(directory.join(format!("id_ed25519_{}.pub", name)), format!("{}\n", ssh_key.public_key))?;
+        write_private_file(&directory.join(format!("age_{}.txt", name)), format!("# public key: {}\n{}\n", recipient, identity).as_bytes())?;
+        Ok(())
+    };
+    match write() {
+        Ok(_) => {
+            println!("Wrote the keys to {}", directory.display());
+            println!("SSH public key: {}", ssh_key.public_key);
+            println!("age recipient: {}", recipient);
+        },
+        Err(e) => println!("Failed to write the keys: {}.", e),
+    }
+}
+
+/// `kg_passgen totp <url> [--enroll]` prints the current TOTP code of the host,
+/// and with `--enroll` the secret, `otpauth://` URI and backup codes to register with the service
+fn totp_command(args: &[String]) {
+    let (url, enroll) = match args {
+        [url] => (url, false),
+        [url, flag] if flag == "--enroll" => (url, true),
+        _ => {
+            println!("Usage: kg_passgen totp <url> [--enroll]");
+            return;
+        }
+    };
+
+    let master_password = prompt_master_password(0);
+    let config = Config::default();
+    report_host(url, &config);
+    let secret = kg_passgen::otp::generate_totp_secret(url, &master_password, &config);
+
+    if enroll {
+        let host = kg_passgen::url_helper::get_config_host(url, &config);
+        println!("TOTP secret: {}", secret);
+        println!("URI: {}", kg_passgen::otp::otpauth_uri(&host, "kg_passgen", &secret));
+        println!("Backup codes:");
+        for code in kg_passgen::otp::generate_backup_codes(url, &master_password, &config, kg_passgen::otp::DEFAULT_BACKUP_CODES) {
+            println!("  {}", code);
+        }
+    }
+
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or_default();
+    if let Some(code) = kg_passgen::otp::totp_code(&secret, now) {
+        println!("Current code: {} (valid for {} more seconds)", code, kg_passgen::otp::TOTP_PERIOD - now % kg_passgen::otp::TOTP_PERIOD);
+    }
+}
+
+/// Warns when the secret appears in the breach dump or filter
+fn report_breach(breach: &kg_passgen::breach::BreachSource, name: &str, secret: &str) {
+    match breach.check(secret) {
+        Ok(kg_passgen::breach::BreachCheck::NotFound) => println!("The {} was not found in the breach data.", name),
+        Ok(kg_passgen::breach::BreachCheck::Found(count)) => println!("Warning: the {} appears {} times in known breaches.", name, count),
+        Ok(kg_passgen::breach::BreachCheck::ProbablyFound) => println!("Warning: the {} probably appears in known breaches.", name),
+        Err(e) => println!("Failed to check the breach data: {}.", e),
+    }
+}
+
+/// `kg_passgen host <url> [--keep-subdomains] [--include-port] [--shared-logins]`
+/// prints how the host used to generate passwords is derived from the URL
+fn host_command(args: &[String]) {
+    let mut config = Config::default();
+    let mut url = None;
+    for arg in args {
+        match arg.as_str() {
+            "--keep-subdomains" => config = config.with_strip_subdomain(false),
+            "--include-port" => config = config.with_include_port(true),
+            "--shared-logins" => config = config.with_equivalences(kg_passgen::url_helper::EquivalenceTable::builtin()),
+            _ if url.is_none() && !arg.starts_with("--") => url = Some(arg),
+            _ => {
+                println!("Unknown argument: {}", arg);
+                url = None;
+                break;
+            }
+        }
+    }
+
+    match url {
+        Some(url) => println!("{}", kg_passgen::url_helper::analyze(url, &config)),
+        None => println!("Usage: kg_passgen host <url> [--keep-subdomains] [--include-port] [--shared-logins]"),
+    }
+}
+
+/// Describes the config in one line with its generator, hash, length or template and hops
+fn config_summary(config: &Config) -> String {
+    let generator = match &config.generator_type {
+        GeneratorType::KGPG => "KGPG",
+        GeneratorType::SGP => "SGP",
+        GeneratorType::LessPass(_) => "LessPass",
+        GeneratorType::Spectre(_) => "Spectre",
+        GeneratorType::PasswordMaker(_) => "PasswordMaker",
+        GeneratorType::Pronounceable(_) => "Pronounceable",
+    };
+    let length = match &config.template {
+        Some(template) => format!("template {}", template),
+        None => format!("length {}", config.length),
+    };
+    format!("{} ({:?}, {}, {} hops)", generator, config.hash_algorithm, length, config.hops)
+}
+
+/// Prompts for one of the presets or a custom configuration
+fn prompt_config() -> Config {
+    let kg_config = config_summary(&kg_passgen::config::Config::KGPG);
+    let sgp_config = config_summary(&kg_passgen::config::Config::SGP);
+    let select_config = Select::new("Select Configuration", vec![&kg_config, &sgp_config, "LessPass", "Spectre", "PasswordMaker", "Pronounceable", "Custom"])
         .with_help_message("Choose the password generation configuration")
         .prompt();
 
-    let config = match select_config {
+    match select_config {
         Ok(choice) => {
             if choice == kg_config {
                 kg_passgen::config::Config::KGPG
             } else if choice == sgp_config {
                 kg_passgen::config::Config::SGP
+            } else if choice == "LessPass" {
+                let login = Text::new("Login:")
+                    .with_help_message("The username or email used on the site, can be left empty")
+                    .prompt()
+                    .expect("Failed to read input");
+
+                let counter: u32 = CustomType::<u32>::new("Counter:")
+                    .with_help_message("Increase to rotate the password for the same site and login")
+                    .with_default(1)
+                    .with_error_message("Please enter a valid number")
+                    .prompt()
+                    .expect("Failed to read input");
+
+                let length: u8 = CustomType::<u8>::new("Password Length:")
+                    .with_help_message("Desired length of the generated password")
+                    .with_default(16)
+                    .with_validator(|input: &u8| {
+                        if (kg_passgen::generator::lesspass::MIN_LENGTH..=kg_passgen::generator::lesspass::MAX_LENGTH).contains(input) {
+                            Ok(Validation::Valid)
+                        } else {
+                            Ok(Validation::Invalid("LessPass passwords must be between 5 and 35 characters.".into()))
+                        }
+                    })
+                    .with_error_message("Please enter a valid number")
+                    .prompt()
+                    .expect("Failed to read input");
+
+                let character_sets = MultiSelect::new("Character Sets:", vec!["Lowercase", "Uppercase", "Digits", "Symbols"])
+                    .with_help_message("Characters allowed in the generated password")
+                    .with_all_selected_by_default()
+                    .with_validator(|selected: &[inquire::list_option::ListOption<&&str>]| {
+                        if selected.is_empty() {
+                            Ok(Validation::Invalid("Select at least one character set.".into()))
+                        } else {
+                            Ok(Validation::Valid)
+                        }
+                    })
+                    .prompt()
+                    .expect("Failed to read input");
+
+                let profile = kg_passgen::config::LessPassProfile::default()
+                    .with_login(&login)
+                    .with_counter(counter)
+                    .with_lowercase(character_sets.contains(&"Lowercase"))
+                    .with_uppercase(character_sets.contains(&"Uppercase"))
+                    .with_digits(character_sets.contains(&"Digits"))
+                    .with_symbols(character_sets.contains(&"Symbols"));
+
+                kg_passgen::config::Config::LESSPASS
+                    .with_generator_type(kg_passgen::config::GeneratorType::LessPass(profile))
+                    .with_length(length)
+            } else if choice == "Spectre" {
+                let full_name = Text::new("Full Name:")
+                    .with_help_message("The full name used in Spectre, it is part of the key derivation")
+                    .with_validator(required!("A full name is required"))
+                    .prompt()
+                    .expect("Failed to read input");
+
+                let counter: u32 = CustomType::<u32>::new("Counter:")
+                    .with_help_message("Increase to rotate the password for the same site")
+                    .with_default(1)
+                    .with_error_message("Please enter a valid number")
+                    .prompt()
+                    .expect("Failed to read input");
+
+                let template_select = Select::new("Select Template", vec!["Maximum", "Long", "Medium", "Short", "Basic", "PIN", "Name", "Phrase"])
+                    .with_help_message("Choose the format of the generated password")
+                    .with_starting_cursor(1)
+                    .prompt()
+                    .expect("Failed to read input");
+
+                let template = match template_select {
+                    "Maximum" => kg_passgen::config::SpectreTemplate::Maximum,
+                    "Medium" => kg_passgen::config::SpectreTemplate::Medium,
+                    "Short" => kg_passgen::config::SpectreTemplate::Short,
+                    "Basic" => kg_passgen::config::SpectreTemplate::Basic,
+                    "PIN" => kg_passgen::config::SpectreTemplate::PIN,
+                    "Name" => kg_passgen::config::SpectreTemplate::Name,
+                    "Phrase" => kg_passgen::config::SpectreTemplate::Phrase,
+                    _ => kg_passgen::config::SpectreTemplate::Long,
+                };
+
+                let profile = kg_passgen::config::SpectreProfile::default()
+                    .with_full_name(&full_name)
+                    .with_counter(counter)
+                    .with_template(template);
+
+                kg_passgen::config::Config::SPECTRE
+                    .with_generator_type(kg_passgen::config::GeneratorType::Spectre(profile))
+            } else if choice == "PasswordMaker" {
+                let algorithm_select = Select::new("Select Hash Algorithm", vec!["MD5", "HMAC-MD5", "SHA1", "HMAC-SHA1", "SHA256", "HMAC-SHA256", "MD4", "HMAC-MD4", "RIPEMD160", "HMAC-RIPEMD160"])
+                    .with_help_message("Choose the hashing algorithm of the PasswordMaker profile")
+                    .prompt()
+                    .expect("Failed to read input");
+
+                let algorithm = match algorithm_select.trim_start_matches("HMAC-") {
+                    "MD4" => kg_passgen::config::PasswordMakerAlgorithm::MD4,
+                    "SHA1" => kg_passgen::config::PasswordMakerAlgorithm::SHA1,
+                    "SHA256" => kg_passgen::config::PasswordMakerAlgorithm::SHA256,
+                    "RIPEMD160" => kg_passgen::config::PasswordMakerAlgorithm::RIPEMD160,
+                    _ => kg_passgen::config::PasswordMakerAlgorithm::MD5,
+                };
+
+                let url_components = MultiSelect::new("URL Components:", vec!["Protocol", "Subdomains", "Domain", "Port and Path"])
+                    .with_help_message("Parts of the URL used to generate the password")
+                    .with_default(&[2])
+                    .prompt()
+                    .expect("Failed to read input");
+
+                let length: u8 = CustomType::<u8>::new("Password Length:")
+                    .with_help_message("Desired length of the generated password")
+                    .with_default(8)
+                    .with_validator(|input: &u8| {
+                        if *input > 0 {
+                            Ok(Validation::Valid)
+                        } else {
+                            Ok(Validation::Invalid("The password must have at least one character.".into()))
+                        }
+                    })
+                    .with_error_message("Please enter a valid number")
+                    .prompt()
+                    .expect("Failed to read input");
+
+                let characters = Text::new("Characters:")
+                    .with_help_message("Characters used in the generated password")
+                    .with_default(kg_passgen::config::PasswordMakerProfile::DEFAULT_CHARACTERS)
+                    .prompt()
+                    .expect("Failed to read input");
+
+                let username = Text::new("Username:")
+                    .with_help_message("Optional username of the profile")
+                    .prompt()
+                    .expect("Failed to read input");
+
+                let modifier = Text::new("Modifier:")
+                    .with_help_message("Optional modifier, change it to rotate the password")
+                    .prompt()
+                    .expect("Failed to read input");
+
+                let prefix = Text::new("Prefix:")
+                    .with_help_message("Optional text prepended to the generated password")
+                    .prompt()
+                    .expect("Failed to read input");
+
+                let suffix = Text::new("Suffix:")
+                    .with_help_message("Optional text appended to the generated password")
+                    .prompt()
+                    .expect("Failed to read input");
+
+                let leet_select = Select::new("Use l33t", vec!["Not at all", "Before hashing", "After hashing", "Before and after hashing"])
+                    .with_help_message("When to apply the l33t replacements")
+                    .prompt()
+                    .expect("Failed to read input");
+
+                let leet_mode = match leet_select {
+                    "Before hashing" => kg_passgen::config::LeetMode::BeforeHashing,
+                    "After hashing" => kg_passgen::config::LeetMode::AfterHashing,
+                    "Before and after hashing" => kg_passgen::config::LeetMode::Both,
+                    _ => kg_passgen::config::LeetMode::NotAtAll,
+                };
+
+                let leet_level: u8 = if leet_mode == kg_passgen::config::LeetMode::NotAtAll {
+                    1
+                } else {
+                    CustomType::<u8>::new("l33t Level:")
+                        .with_help_message("Level of the l33t replacements, from 1 to 9")
+                        .with_default(1)
+                        .with_validator(|input: &u8| {
+                            if (1..=9).contains(input) {
+                                Ok(Validation::Valid)
+                            } else {
+                                Ok(Validation::Invalid("The l33t level must be between 1 and 9.".into()))
+                            }
+                        })
+                        .with_error_message("Please enter a valid number")
+                        .prompt()
+                        .expect("Failed to read input")
+                };
+
+                let profile = kg_passgen::config::PasswordMakerProfile::default()
+                    .with_algorithm(algorithm)
+                    .with_hmac(algorithm_select.starts_with("HMAC-"))
+                    .with_url_components(kg_passgen::url_helper::UrlComponents {
+                        protocol: url_components.contains(&"Protocol"),
+                        subdomains: url_components.contains(&"Subdomains"),
+                        domain: url_components.contains(&"Domain"),
+                        port_path: url_components.contains(&"Port and Path"),
+                    })
+                    .with_characters(&characters)
+                    .with_username(&username)
+                    .with_modifier(&modifier)
+                    .with_prefix(&prefix)
+                    .with_suffix(&suffix)
+                    .with_leet(leet_mode, leet_level);
+
+                kg_passgen::config::Config::PASSWORDMAKER
+                    .with_generator_type(kg_passgen::config::GeneratorType::PasswordMaker(profile))
+                    .with_length(length)
+            } else if choice == "Pronounceable" {
+                let length: u8 = CustomType::<u8>::new("Password Length:")
+                    .with_help_message("Desired length of the generated password, including the digits and symbol")
+                    .with_default(kg_passgen::config::Config::PRONOUNCEABLE.length)
+                    .with_validator(|input: &u8| {
+                        if (8..=84).contains(input) {
+                            Ok(Validation::Valid)
+                        } else {
+                            Ok(Validation::Invalid("Pronounceable passwords must be between 8 and 84 characters.".into()))
+                        }
+                    })
+                    .with_error_message("Please enter a valid number")
+                    .prompt()
+                    .expect("Failed to read input");
+
+                let capitalize = Confirm::new("Capitalize?")
+                    .with_help_message("Whether the first letter is uppercase")
+                    .with_default(true)
+                    .prompt()
+                    .expect("Failed to read input");
+
+                let symbol = Confirm::new("Symbol?")
+                    .with_help_message("Whether one of !#%@$& ends the password")
+                    .with_default(true)
+                    .prompt()
+                    .expect("Failed to read input");
+
+                let max_digits = length - 2 - symbol as u8;
+                let digits: u8 = CustomType::<u8>::new("Digits:")
+                    .with_help_message("Number of digits appended after the syllables")
+                    .with_default(2.min(max_digits))
+                    .with_validator(move |input: &u8| {
+                        if *input <= max_digits {
+                            Ok(Validation::Valid)
+                        } else {
+                            Ok(Validation::Invalid(format!("Leave room for at least two syllable letters{}, at most {} digits.", if symbol { " and the symbol" } else { "" }, max_digits).into()))
+                        }
+                    })
+                    .with_error_message("Please enter a valid number")
+                    .prompt()
+                    .expect("Failed to read input");
+
+                let profile = kg_passgen::config::PronounceableProfile::default()
+                    .with_capitalize(capitalize)
+                    .with_digits(digits)
+                    .with_symbol(symbol);
+
+                kg_passgen::config::Config::PRONOUNCEABLE
+                    .with_generator_type(kg_passgen::config::GeneratorType::Pronounceable(profile))
+                    .with_length(length)
             } else {
                 let strip_domain = Confirm::new("Strip Subdomain?")
                     .with_help_message("Whether to remove subdomains from the URL host")
@@ -45,6 +860,12 @@ fn main() {
                     .prompt()
                     .expect("Failed to read input");
 
+                let include_port = Confirm::new("Include Port?")
+                    .with_help_message("Whether services on different ports of the same host get different passwords")
+                    .with_default(false)
+                    .prompt()
+                    .expect("Failed to read input");
+
                 let hash_algorithm_select = Select::new("Select Hash Algorithm", vec!["SHA512", "MD5"])
                     .with_help_message("Choose the hashing algorithm for password generation")
                     .prompt()
@@ -68,13 +889,34 @@ fn main() {
                     }
                 };
 
-                let length: u8 = CustomType::<u8>::new("Password Length:")
-                    .with_help_message("Desired length of the generated password")
-                    .with_validator(number_input_validtion)
-                    .with_error_message("Please enter a valid number")
-                    .with_placeholder("e.g., 15")
+                let template_hash_algorithm = hash_algorithm.clone();
+                let template = Text::new("Template:")
+                    .with_help_message("Optional format such as A{4}-9{4}-a{4}: a lower, A upper, 9 digit, s symbol, {n} repeats, \\ escapes")
+                    .with_validator(move |input: &str| {
+                        if input.trim().is_empty() {
+                            return Ok(Validation::Valid);
+                        }
+                        let checked = kg_passgen::template::Template::parse(input.trim()).and_then(|template| template.check_hash(&template_hash_algorithm));
+                        match checked {
+                            Ok(_) => Ok(Validation::Valid),
+                            Err(e) => Ok(Validation::Invalid(e.to_string().into())),
+                        }
+                    })
                     .prompt()
                     .expect("Failed to read input");
+                let template = kg_passgen::template::Template::parse(template.trim()).ok();
+
+                // The template defines the length
+                let length: u8 = match &template {
+                    Some(template) => template.len().min(u8::MAX as usize) as u8,
+                    None => CustomType::<u8>::new("Password Length:")
+                        .with_help_message("Desired length of the generated password")
+                        .with_validator(number_input_validtion)
+                        .with_error_message("Please enter a valid number")
+                        .with_placeholder("e.g., 15")
+                        .prompt()
+                        .expect("Failed to read input"),
+                };
 
                 let hops: u8 =  CustomType::<u8>::new("Number of Hops:")
                     .with_help_message("Number of hashing iterations to apply")
@@ -83,22 +925,122 @@ fn main() {
                     .prompt()
                     .expect("Failed to read input");
 
-                kg_passgen::config::Config::default()
+                let suffix_list = if strip_domain { prompt_suffix_list() } else { kg_passgen::suffix_list::SuffixList::Builtin };
+
+                let config = kg_passgen::config::Config::default()
                     .with_hash_algorithm(hash_algorithm)
                     .with_length(length)
                     .with_hops(hops)
                     .with_strip_subdomain(strip_domain)
+                    .with_include_port(include_port)
+                    .with_suffix_list(suffix_list);
+                match template {
+                    Some(template) => config.with_template(template),
+                    None => config,
+                }
             }
         },
         Err(_) => {
             println!("Error selecting configuration, defaulting to KGPG.");
             kg_passgen::config::Config::KGPG
         }
+    }
+}
+
+/// Asks whether to use the built-in public suffix list or to load one from disk
+fn prompt_suffix_list() -> kg_passgen::suffix_list::SuffixList {
+    let source = Select::new("Public Suffix List", vec!["Built-in", "Load from file"])
+        .with_help_message("The list used to strip subdomains, a different list can change some passwords")
+        .prompt()
+        .expect("Failed to read input");
+
+    if source != "Load from file" {
+        return kg_passgen::suffix_list::SuffixList::Builtin;
+    }
+
+    let path = Text::new("List File:")
+        .with_placeholder("e.g., public_suffix_list.dat")
+        .with_validator(required!("A file is required"))
+        .prompt()
+        .expect("Failed to read input");
+
+    let include_private = Confirm::new("Include Private Domains?")
+        .with_help_message("Whether hosting suffixes such as github.io give each site its own password")
+        .with_default(true)
+        .prompt()
+        .expect("Failed to read input");
+
+    match kg_passgen::suffix_list::PublicSuffixList::load(path.trim(), include_private) {
+        Ok(list) => kg_passgen::suffix_list::SuffixList::Loaded(list),
+        Err(e) => {
+            println!("Failed to read the public suffix list, using the built-in list: {}.", e);
+            kg_passgen::suffix_list::SuffixList::Builtin
+        }
+    }
+}
+
+/// Asks whether shared-login domains map to one host and loads the optional groups file
+fn prompt_equivalences(config: Config) -> Config {
+    let use_groups = Confirm::new("Treat shared-login domains as one site?")
+        .with_help_message("Maps domains such as youtube.com and gmail.com to one canonical host, changes their passwords")
+        .with_default(false)
+        .prompt()
+        .expect("Failed to read input");
+
+    if !use_groups {
+        return config;
+    }
+
+    let groups_file = Text::new("Groups File:")
+        .with_help_message("Optional file with one group per line, hosts separated by spaces or commas")
+        .prompt()
+        .expect("Failed to read input");
+
+    let builtin = kg_passgen::url_helper::EquivalenceTable::builtin();
+    let equivalences = if groups_file.trim().is_empty() {
+        builtin
+    } else {
+        match builtin.clone().with_file(groups_file.trim()) {
+            Ok(equivalences) => equivalences,
+            Err(e) => {
+                println!("Failed to read the groups file, using the built-in groups only: {}.", e);
+                builtin
+            }
+        }
     };
+    config.with_equivalences(equivalences)
+}
 
-    println!("Current configuration is: {:?}", config);
+/// Asks for an optional app mappings file when the service URL is an app identifier
+fn prompt_apps(config: Config, url: &str) -> Config {
+    if kg_passgen::url_helper::interpret(url).app.is_none() {
+        return config;
+    }
+
+    let mappings_file = Text::new("App Mappings File:")
+        .with_help_message("Optional file with a package name and web domain per line, leave empty for the built-in mappings")
+        .prompt()
+        .expect("Failed to read input");
+
+    if mappings_file.trim().is_empty() {
+        return config;
+    }
+    match kg_passgen::url_helper::AppTable::EMPTY.with_file(mappings_file.trim()) {
+        Ok(apps) => config.with_apps(apps),
+        Err(e) => {
+            println!("Failed to read the app mappings file, using the built-in mappings only: {}.", e);
+            config
+        }
+    }
+}
+
+fn password_mode(clipboard: &mut Clipboard, url: &str, master_password: &str, breach: Option<&kg_passgen::breach::BreachSource>) {
+    let config = prompt_apps(prompt_equivalences(prompt_config()), url);
+    println!("Current configuration is: {}", config_summary(&config));
+    println!("Public suffix list version: {}", config.suffix_list.version());
+    report_host(url, &config);
 
-    let generated_password = match kg_passgen::generator::generate_password(&url, &master_password, &config) {
+    let generated_password = match kg_passgen::generator::generate_password(url, master_password, &config) {
         Ok(pw) => pw,
         Err(e) => {
             println!("Error generating password: {}.", e);
@@ -106,27 +1048,110 @@ fn main() {
         }
     };
 
-    match clipboard.set_text(generated_password.clone()) {
+    let report = kg_passgen::generator::validate_password_report(&generated_password, &config);
+    println!("Estimated entropy of this configuration: {:.0} bits", report.entropy_bits);
+    if report.entropy_bits < kg_passgen::generator::RECOMMENDED_ENTROPY_BITS {
+        println!("Warning: weak configuration, use a longer password or a larger alphabet for at least {:.0} bits.", kg_passgen::generator::RECOMMENDED_ENTROPY_BITS);
+    }
+    if !report.is_valid() {
+        println!("Warning: the generated password fails the rules {:?}.", report.failed());
+    }
+    if let Some(breach) = breach {
+        report_breach(breach, "generated password", &generated_password);
+    }
+    output_secret(clipboard, "password", &generated_password);
+}
+
+fn username_mode(clipboard: &mut Clipboard, url: &str, master_password: &str) {
+    let style_select = Select::new("Select Username Style", vec!["Word pair", "Hex tag", "Plus address"])
+        .with_help_message("Choose the format of the generated username")
+        .prompt()
+        .expect("Failed to read input");
+
+    let style = match style_select {
+        "Hex tag" => kg_passgen::username::UsernameStyle::HexTag,
+        "Plus address" => {
+            let email = Text::new("Email Address:")
+                .with_placeholder("e.g., user@example.com")
+                .with_help_message("A tag is added to the local part, your provider must support plus addressing")
+                .with_validator(required!("An email address is required"))
+                .prompt()
+                .expect("Failed to read input");
+            kg_passgen::username::UsernameStyle::PlusAddress(email)
+        },
+        _ => kg_passgen::username::UsernameStyle::WordPair,
+    };
+
+    let config = prompt_apps(prompt_equivalences(Config::default()), url);
+    report_host(url, &config);
+
+    let generated_username = match kg_passgen::username::generate_username(url, master_password, &style, &config) {
+        Ok(username) => username,
+        Err(e) => {
+            println!("Error generating username: {}.", e);
+            return;
+        }
+    };
+
+    output_secret(clipboard, "username", &generated_username);
+}
+
+fn answer_mode(clipboard: &mut Clipboard, url: &str, master_password: &str) {
+    let question = Text::new("Security Question:")
+        .with_placeholder("e.g., What is your mother's maiden name?")
+        .with_help_message("Case, spacing and punctuation are ignored")
+        .with_validator(required!("A question is required"))
+        .prompt()
+        .expect("Failed to read input");
+
+    let style = match Select::new("Select Answer Style", vec!["Words", "Pronounceable"])
+        .with_help_message("Choose the format of the generated answer")
+        .prompt()
+        .expect("Failed to read input")
+    {
+        "Pronounceable" => kg_passgen::answer::AnswerStyle::Pronounceable,
+        _ => kg_passgen::answer::AnswerStyle::Words,
+    };
+
+    let config = prompt_apps(prompt_equivalences(Config::default()), url);
+    report_host(url, &config);
+    println!("Using question: {}", kg_passgen::answer::normalize_question(&question));
+
+    let generated_answer = kg_passgen::answer::generate_answer_with_style(url, &question, master_password, style, &config);
+    output_secret(clipboard, "answer", &generated_answer);
+}
+
+/// Prints how the service URL was interpreted and which host is used
+fn report_host(url: &str, config: &Config) {
+    match kg_passgen::url_helper::interpret(url).interpretation {
+        kg_passgen::url_helper::Interpretation::Url => {},
+        kg_passgen::url_helper::Interpretation::SchemeAdded => println!("No scheme given, the service URL was read as https://{}", url.trim()),
+        kg_passgen::url_helper::Interpretation::Raw => println!("The service URL is not a valid URL, it is used as the host as is."),
+        kg_passgen::url_helper::Interpretation::App => println!("The service URL is an app identifier, it is mapped to the web domain of the app."),
+    }
+    println!("Using host: {}", kg_passgen::url_helper::get_config_host(url, config));
+}
+
+/// Copies the generated value to the clipboard and only prints it when asked to
+fn output_secret(clipboard: &mut Clipboard, name: &str, secret: &str) {
+    match clipboard.set_text(secret.to_string()) {
         Ok(_) => {
-            println!("Generated password copied to clipboard!");
+            println!("Generated {} copied to clipboard!", name);
         },
         Err(e) => {
             println!("Failed to copy to clipboard: {}.", e);
         }
     }
 
-    let show_password = Confirm::new("Show generated password?")
-        .with_help_message("Choose whether to display the generated password in the console")
+    let show_secret = Confirm::new(&format!("Show generated {}?", name))
+        .with_help_message(&format!("Choose whether to display the generated {} in the console", name))
         .with_default(false)
         .prompt()
         .expect("Failed to read input");
 
-    if show_password {
-        println!("Generated password: \n{}", generated_password.trim());
+    if show_secret {
+        println!("Generated {}: \n{}", name, secret.trim());
     } else {
-        println!("Password not displayed.")
+        println!("Generated {} not displayed.", name)
     }
-
-    println!("Press Enter to exit...");
-    io::stdin().read_line(&mut String::new()).expect("Failed to detect input. Exiting");
 }
@@ -0,0 +1,147 @@
+//! Derives recovery material for two-factor enrollment: a Base32 TOTP secret and one-time backup codes per site.
+//! Uses the `generator::derive_bytes` labels `totp` and `backup-codes`.
+//! Codes are computed as in RFC 6238 with HMAC-SHA1, 6 digits and a 30 second period, the defaults of authenticator apps.
+//!
+//! # Examples
+//! ```
+//! use kg_passgen::config::Config;
+//! use kg_passgen::otp::{generate_backup_codes, generate_totp_secret, totp_code};
+//! let config = Config::default();
+//!
+//! let secret = generate_totp_secret("https://example.com", "my_master_password", &config);
+//! assert_eq!(secret.len(), 32);
+//! assert_eq!(totp_code(&secret, 1_700_000_000).unwrap().len(), 6);
+//!
+//! let codes = generate_backup_codes("https://example.com", "my_master_password", &config, 10);
+//! assert_eq!(codes.len(), 10);
+//! assert_eq!(codes[0].len(), 11);
+//! ```
+
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use sha2::Sha256;
+
+use crate::config::Config;
+use crate::generator::derive_bytes;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Seconds each code is valid for
+pub const TOTP_PERIOD: u64 = 30;
+pub const TOTP_DIGITS: u32 = 6;
+/// Number of backup codes the CLI prints
+pub const DEFAULT_BACKUP_CODES: usize = 10;
+
+/// Encodes bytes in RFC 4648 Base32 without padding, as authenticator apps expect
+pub fn base32_encode(bytes: &[u8]) -> String {
+    let bits: Vec<bool> = bytes.iter().flat_map(|byte| (0..8).rev().map(move |i| byte >> i & 1 == 1)).collect();
+    bits.chunks(5).map(|chunk| {
+        let index = (0..5).fold(0, |index, i| index << 1 | chunk.get(i).copied().unwrap_or(false) as usize);
+        BASE32_ALPHABET[index] as char
+    }).collect()
+}
+
+/// Decodes RFC 4648 Base32, ignoring case, spaces, dashes and padding
+pub fn base32_decode(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in text.chars().filter(|c| !matches!(c, ' ' | '-' | '=')) {
+        let value = BASE32_ALPHABET.iter().position(|&a| a as char == c.to_ascii_uppercase())? as u32;
+        buffer = (buffer << 5 | value) & 0xFFFF;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// Computes an RFC 4226 HOTP code for the counter
+pub fn hotp(key: &[u8], counter: u64, digits: u32) -> String {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key).unwrap();
+    mac.update(&counter.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let offset = (digest[19] & 0x0F) as usize;
+    let value = u32::from_be_bytes(digest[offset..offset + 4].try_into().unwrap()) & 0x7FFF_FFFF;
+    format!("{:0width$}", value as u64 % 10u64.pow(digits), width = digits as usize)
+}
+
+/// Computes the RFC 6238 TOTP code of a Base32 secret at the Unix time, `None` when the secret is not Base32
+pub fn totp_code(secret: &str, unix_time: u64) -> Option<String> {
+    Some(hotp(&base32_decode(secret)?, unix_time / TOTP_PERIOD, TOTP_DIGITS))
+}
+
+/// Derives the Base32 TOTP secret of the service URL, 160 bits as recommended by RFC 4226
+pub fn generate_totp_secret(url: &str, master_password: &str, config: &Config) -> String {
+    let host = crate::url_helper::get_config_host(url, config);
+    base32_encode(&derive_bytes("totp", master_password, &host)[..20])
+}
+
+/// Derives backup codes for the service URL, each with 10 digits as `12345-67890`
+pub fn generate_backup_codes(url: &str, master_password: &str, config: &Config, count: usize) -> Vec<String> {
+    let host = crate::url_helper::get_config_host(url, config);
+    let seed = derive_bytes("backup-codes", master_password, &host);
+    (0..count as u64).map(|index| {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&seed).unwrap();
+        mac.update(&index.to_be_bytes());
+        let code = u64::from_be_bytes(mac.finalize().into_bytes()[..8].try_into().unwrap()) % 10_000_000_000;
+        format!("{:05}-{:05}", code / 100_000, code % 100_000)
+    }).collect()
+}
+
+/// Returns the `otpauth://` URI of the secret, which authenticator apps read from a QR code
+/// # Examples
+/// ```
+/// use kg_passgen::otp::otpauth_uri;
+/// assert_eq!(otpauth_uri("example.com", "me@mail.com", "JBSWY3DPEHPK3PXP"),
+///     "otpauth://totp/example.com:me%40mail.com?secret=JBSWY3DPEHPK3PXP&issuer=example.com&algorithm=SHA1&digits=6&period=30");
+/// ```
+pub fn otpauth_uri(issuer: &str, account: &str, secret: &str) -> String {
+    let encode = |text: &str| url::form_urlencoded::byte_serialize(text.as_bytes()).collect::<String>().replace('+', "%20");
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        encode(issuer), encode(account), secret, encode(issuer), TOTP_DIGITS, TOTP_PERIOD,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hotp_and_totp_match_rfc_vectors() {
+        let key = b"12345678901234567890";
+        let expected = ["755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583", "399871", "520489"];
+        for (counter, code) in expected.iter().enumerate() {
+            assert_eq!(hotp(key, counter as u64, 6), *code);
+        }
+        assert_eq!(hotp(key, 59 / 30, 8), "94287082");
+        assert_eq!(hotp(key, 1_111_111_109 / 30, 8), "07081804");
+        assert_eq!(totp_code(&base32_encode(key), 59), Some("287082".to_string()));
+        assert_eq!(totp_code("not base32!", 59), None);
+    }
+
+    #[test]
+    fn test_base32_round_trip() {
+        assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(base32_decode("mzxw6ytboi======"), Some(b"foobar".to_vec()));
+        for length in 0..24 {
+            let bytes: Vec<u8> = (0..length).map(|i| (i * 37) as u8).collect();
+            assert_eq!(base32_decode(&base32_encode(&bytes)), Some(bytes));
+        }
+    }
+
+    #[test]
+    fn test_derivations_are_separated() {
+        let config = Config::default();
+        let secret = generate_totp_secret("https://example.com", "my_master_password", &config);
+        assert_eq!(secret, generate_totp_secret("https://login.example.com", "my_master_password", &config));
+        assert_ne!(secret, generate_totp_secret("https://example.org", "my_master_password", &config));
+
+        let codes = generate_backup_codes("https://example.com", "my_master_password", &config, 20);
+        assert_eq!(codes[..5], generate_backup_codes("https://example.com", "my_master_password", &config, 5)[..]);
+        assert_eq!(codes.iter().collect::<std::collections::HashSet<_>>().len(), 20);
+    }
+}
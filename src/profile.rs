@@ -0,0 +1,167 @@
+//! Site profiles: the services someone generates passwords for, with their usernames and groups.
+//! Profiles hold no secrets, passwords are always derived again from the master password.
+//! The store is saved as JSON, and plain site lists with one `url,username,group` line per site can be read too.
+//! The store records the version of the public suffix list it was created with, see `suffix_list`.
+//! Stores can be saved encrypted with age, as they reveal which services someone uses.
+//!
+//! # Examples
+//! ```
+//! use kg_passgen::profile::{ProfileStore, SiteProfile};
+//! let store = ProfileStore::from_site_list("# my sites\nhttps://example.com,me@mail.com,Work\ngithub.com");
+//! assert_eq!(store.sites.len(), 2);
+//! assert_eq!(store.sites[0], SiteProfile::new("https://example.com").with_username("me@mail.com").with_group("Work"));
+//!
+//! let json = store.to_json();
+//! assert_eq!(ProfileStore::from_json(&json).unwrap(), store);
+//! ```
+
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::encryption::{decrypt, encrypt, is_encrypted, AgeKey};
+
+/// Version of the JSON layout of the store
+pub const STORE_VERSION: u32 = 1;
+
+/// A service someone generates a password for
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct SiteProfile {
+    pub url: String,
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub group: String,
+    #[serde(default)]
+    pub notes: String,
+}
+
+impl SiteProfile {
+    pub fn new(url: &str) -> Self {
+        SiteProfile { url: url.trim().to_string(), ..Default::default() }
+    }
+
+    pub fn with_username(mut self, username: &str) -> Self { self.username = username.trim().to_string() ; self }
+    pub fn with_group(mut self, group: &str) -> Self { self.group = group.trim().to_string() ; self }
+    pub fn with_notes(mut self, notes: &str) -> Self { self.notes = notes.to_string() ; self }
+}
+
+/// The list of site profiles
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ProfileStore {
+    pub version: u32,
+    /// Version of the public suffix list the store was created with, empty when not recorded
+    #[serde(default)]
+    pub suffix_list_version: String,
+    #[serde(default)]
+    pub sites: Vec<SiteProfile>,
+}
+
+impl Default for ProfileStore {
+    fn default() -> Self {
+        ProfileStore { version: STORE_VERSION, suffix_list_version: String::new(), sites: Vec::new() }
+    }
+}
+
+impl ProfileStore {
+    pub fn with_site(mut self, site: SiteProfile) -> Self { self.sites.push(site) ; self }
+    pub fn with_suffix_list_version(mut self, version: &str) -> Self { self.suffix_list_version = version.to_string() ; self }
+
+    /// Reads a site list: one `url,username,group` line per site, the username and group are optional
+    /// and everything after `#` is a comment
+    pub fn from_site_list(contents: &str) -> Self {
+        contents.lines().fold(ProfileStore::default(), |store, line| {
+            let line = line.split('#').next().unwrap_or_default();
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            match fields[..] {
+                [""] => store,
+                [url] => store.with_site(SiteProfile::new(url)),
+                [url, username] => store.with_site(SiteProfile::new(url).with_username(username)),
+                [url, username, group, ..] => store.with_site(SiteProfile::new(url).with_username(username).with_group(group)),
+                [] => store,
+            }
+        })
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Reads a JSON store, or a site list when the contents are not JSON
+    pub fn from_contents(contents: &str) -> io::Result<Self> {
+        if contents.trim_start().starts_with('{') {
+            ProfileStore::from_json(contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        } else {
+            Ok(ProfileStore::from_site_list(contents))
+        }
+    }
+
+    /// Reads a JSON store, or a site list when the file is not JSON
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        ProfileStore::from_contents(&std::fs::read_to_string(path)?)
+    }
+
+    /// Reads a store encrypted with `save_encrypted`, or a plain store or site list
+    pub fn load_encrypted(path: impl AsRef<Path>, key: &AgeKey) -> io::Result<Self> {
+        let data = std::fs::read(path)?;
+        let data = match is_encrypted(&data) {
+            true => decrypt(&data, key).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?,
+            false => data,
+        };
+        ProfileStore::from_contents(&String::from_utf8(data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+
+    /// Writes the store as an age file, see `encryption`
+    pub fn save_encrypted(&self, path: impl AsRef<Path>, key: &AgeKey) -> io::Result<()> {
+        let data = encrypt(self.to_json().as_bytes(), key).map_err(|e| io::Error::other(e.to_string()))?;
+        std::fs::write(path, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_site_list_skips_comments_and_blank_lines() {
+        let store = ProfileStore::from_site_list("\n# comment\n  example.com  \nmail.com, me \n\nshop.com,,Shopping # note\n");
+        assert_eq!(store.sites, vec![
+            SiteProfile::new("example.com"),
+            SiteProfile::new("mail.com").with_username("me"),
+            SiteProfile::new("shop.com").with_group("Shopping"),
+        ]);
+    }
+
+    #[test]
+    fn test_json_defaults_missing_fields() {
+        let store = ProfileStore::from_json(r#"{"version": 1, "sites": [{"url": "example.com"}]}"#).unwrap();
+        assert_eq!(store.sites, vec![SiteProfile::new("example.com")]);
+        assert!(store.suffix_list_version.is_empty());
+        assert!(ProfileStore::from_json("{").is_err());
+    }
+
+    #[test]
+    fn test_encrypted_store_round_trip() {
+        let path = std::env::temp_dir().join(format!("kg_passgen_store_{}.json.age", std::process::id()));
+        let store = ProfileStore::default().with_site(SiteProfile::new("example.com").with_username("me"));
+        let key = AgeKey::derived("my_master_password");
+
+        store.save_encrypted(&path, &key).unwrap();
+        assert!(!std::fs::read_to_string(&path).unwrap_or_default().contains("example.com"));
+        assert_eq!(ProfileStore::load_encrypted(&path, &key).unwrap(), store);
+        assert!(ProfileStore::load_encrypted(&path, &AgeKey::derived("other")).is_err());
+
+        store.save(&path).unwrap();
+        assert_eq!(ProfileStore::load_encrypted(&path, &key).unwrap(), store);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
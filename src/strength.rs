@@ -0,0 +1,585 @@
+//! Estimates the strength of master passwords, in the style of zxcvbn.
+//! The password is matched against embedded dictionaries (with l33t substitutions, reversed words
+//! and capitalization), keyboard walks, sequences, repeats and dates. The cheapest way to cover
+//! the whole password with matches and brute force gives the estimated number of guesses.
+//!
+//! # Examples
+//! ```
+//! use kg_passgen::strength::estimate_strength;
+//! let weak = estimate_strength("P@ssw0rd1990");
+//! assert!(weak.score < 2);
+//! assert!(!weak.feedback.is_empty());
+//!
+//! let strong = estimate_strength("velvet-tractor-quietly-93-orbits");
+//! assert_eq!(strong.score, 4);
+//! ```
+
+use std::collections::HashMap;
+
+/// Scores below this are reported as weak by the CLI
+pub const RECOMMENDED_SCORE: u8 = 3;
+
+/// Longer passwords are only matched up to this length and the rest is brute force, as in zxcvbn
+pub const MAX_ANALYZED_LENGTH: usize = 100;
+
+const MIN_YEAR_SPACE: i32 = 20;
+const BRUTEFORCE_CARDINALITY: f64 = 10.0;
+const MIN_GUESSES_SINGLE_CHAR: f64 = 10.0;
+const MIN_GUESSES_MULTI_CHAR: f64 = 50.0;
+
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456", "password", "12345678", "qwerty", "123456789", "12345", "1234", "111111", "1234567", "dragon",
+    "123123", "baseball", "abc123", "football", "monkey", "letmein", "696969", "shadow", "master", "666666",
+    "qwertyuiop", "123321", "mustang", "1234567890", "michael", "654321", "superman", "1qaz2wsx", "7777777", "121212",
+    "000000", "qazwsx", "123qwe", "killer", "trustno1", "jordan", "jennifer", "zxcvbnm", "asdfgh", "hunter",
+    "buster", "soccer", "harley", "batman", "andrew", "tigger", "sunshine", "iloveyou", "2000", "charlie",
+    "robert", "thomas", "hockey", "ranger", "daniel", "starwars", "klaster", "112233", "george", "computer",
+    "michelle", "jessica", "pepper", "1111", "zxcvbn", "555555", "11111111", "131313", "freedom", "777777",
+    "pass", "maggie", "159753", "aaaaaa", "ginger", "princess", "joshua", "cheese", "amanda", "summer",
+    "love", "ashley", "nicole", "chelsea", "biteme", "matthew", "access", "yankees", "987654321", "dallas",
+    "austin", "thunder", "taylor", "matrix", "mobilemail", "mom", "monitor", "monitoring", "montana", "moon",
+    "moscow", "welcome", "admin", "passw0rd", "password1", "qwerty123", "iloveyou1", "secret", "login", "solo",
+    "starwars1", "whatever", "donald", "flower", "hottie", "loveme", "zaq1zaq1", "asdfghjkl", "azerty", "trustme",
+];
+
+const ENGLISH_WORDS: &[&str] = &[
+    "the", "and", "you", "that", "was", "for", "are", "with", "his", "they",
+    "one", "have", "this", "from", "word", "but", "what", "some", "can", "out",
+    "other", "were", "all", "there", "when", "use", "your", "how", "said", "each",
+    "she", "which", "their", "time", "will", "way", "about", "many", "then", "them",
+    "write", "would", "like", "these", "her", "long", "make", "thing", "see", "him",
+    "two", "has", "look", "more", "day", "could", "come", "did", "number", "sound",
+    "most", "people", "over", "know", "water", "than", "call", "first", "who", "may",
+    "down", "side", "been", "now", "find", "any", "new", "work", "part", "take",
+    "get", "place", "made", "live", "where", "after", "back", "little", "only", "round",
+    "man", "year", "came", "show", "every", "good", "give", "our", "under", "name",
+    "very", "through", "just", "form", "great", "think", "say", "help", "low", "line",
+    "before", "turn", "cause", "same", "mean", "differ", "move", "right", "boy", "old",
+    "too", "does", "tell", "sentence", "set", "three", "want", "air", "well", "also",
+    "play", "small", "end", "put", "home", "read", "hand", "port", "large", "spell",
+    "add", "even", "land", "here", "must", "big", "high", "such", "follow", "act",
+    "why", "ask", "men", "change", "went", "light", "kind", "off", "need", "house",
+    "picture", "try", "again", "animal", "point", "mother", "world", "near", "build", "self",
+    "earth", "father", "head", "stand", "own", "page", "should", "country", "found", "answer",
+    "school", "grow", "study", "still", "learn", "plant", "cover", "food", "sun", "four",
+    "thought", "let", "keep", "eye", "never", "last", "door", "between", "city", "tree",
+    "cross", "since", "hard", "start", "might", "story", "saw", "far", "sea", "draw",
+    "left", "late", "run", "while", "press", "close", "night", "real", "life", "few",
+    "stop", "open", "seem", "together", "next", "white", "children", "begin", "got", "walk",
+    "example", "ease", "paper", "often", "always", "music", "those", "both", "mark", "book",
+    "letter", "until", "mile", "river", "car", "feet", "care", "second", "group", "carry",
+    "took", "rain", "eat", "room", "friend", "began", "idea", "fish", "mountain", "north",
+    "once", "base", "hear", "horse", "cut", "sure", "watch", "color", "face", "wood",
+    "main", "enough", "plain", "girl", "usual", "young", "ready", "above", "ever", "red",
+    "list", "though", "feel", "talk", "bird", "soon", "body", "dog", "family", "direct",
+    "pose", "leave", "song", "measure", "state", "product", "black", "short", "numeral", "class",
+    "wind", "question", "happen", "complete", "ship", "area", "half", "rock", "order", "fire",
+    "south", "problem", "piece", "told", "knew", "pass", "farm", "top", "whole", "king",
+    "size", "heard", "best", "hour", "better", "true", "during", "hundred", "am", "remember",
+    "step", "early", "hold", "west", "ground", "interest", "reach", "fast", "five", "sing",
+    "listen", "six", "table", "travel", "less", "morning", "ten", "simple", "several", "vowel",
+    "toward", "war", "lay", "against", "pattern", "slow", "center", "love", "person", "money",
+    "serve", "appear", "road", "map", "science", "rule", "govern", "pull", "cold", "notice",
+    "voice", "fall", "power", "town", "fine", "certain", "fly", "unit", "lead", "cry",
+    "dark", "machine", "note", "wait", "plan", "figure", "star", "box", "noun", "field",
+    "rest", "correct", "able", "pound", "done", "beauty", "drive", "stood", "contain", "front",
+    "teach", "week", "final", "gave", "green", "quick", "develop", "sleep", "warm", "free",
+    "minute", "strong", "special", "mind", "behind", "clear", "tail", "produce", "fact", "street",
+    "inch", "lot", "nothing", "course", "stay", "wheel", "full", "force", "blue", "object",
+    "decide", "surface", "deep", "moon", "island", "foot", "yet", "busy", "test", "record",
+    "boat", "common", "gold", "possible", "plane", "age", "dry", "wonder", "laugh", "thousand",
+    "ago", "ran", "check", "game", "shape", "yes", "hot", "miss", "brought", "heat",
+    "snow", "bed", "bring", "sit", "perhaps", "fill", "east", "weight", "language", "among",
+    "battery", "staple", "dragon", "secret", "summer", "winter", "spring", "autumn", "monday", "friday",
+    "apple", "banana", "orange", "cherry", "coffee", "chocolate", "cookie", "pizza", "tiger", "lion",
+    "monkey", "turtle", "rabbit", "purple", "yellow", "silver", "golden", "diamond", "angel", "heaven",
+    "soccer", "hockey", "tennis", "guitar", "piano", "happy", "lucky", "magic", "super", "hello",
+];
+
+const KEYBOARD_ROWS: [[&str; 4]; 2] = [
+    ["1234567890-=", "qwertyuiop[]\\", "asdfghjkl;'", "zxcvbnm,./"],
+    ["!@#$%^&*()_+", "QWERTYUIOP{}|", "ASDFGHJKL:\"", "ZXCVBNM<>?"],
+];
+const KEYBOARD_STARTING_POSITIONS: f64 = 94.0;
+const KEYBOARD_AVERAGE_DEGREE: f64 = 4.6;
+
+const L33T_TABLES: [&[(char, char)]; 2] = [
+    &[('4', 'a'), ('@', 'a'), ('8', 'b'), ('(', 'c'), ('3', 'e'), ('6', 'g'), ('1', 'i'), ('!', 'i'), ('0', 'o'), ('$', 's'), ('5', 's'), ('7', 't'), ('+', 't'), ('2', 'z')],
+    &[('4', 'a'), ('@', 'a'), ('8', 'b'), ('(', 'c'), ('3', 'e'), ('6', 'g'), ('1', 'l'), ('|', 'l'), ('0', 'o'), ('$', 's'), ('5', 's'), ('7', 't'), ('+', 't'), ('2', 'z')],
+];
+
+/// Kind of a matched part of the password
+#[derive(Debug, PartialEq, Clone)]
+pub enum Pattern {
+    /// A dictionary entry, `rank` is its position in the dictionary
+    Dictionary { word: String, rank: usize, common_password: bool, l33t: bool, reversed: bool },
+    /// Adjacent keys on a QWERTY keyboard
+    Spatial,
+    /// Characters with a constant step such as `abc` or `9753`
+    Sequence,
+    /// A repeated character or chunk such as `aaa` or `abcabc`
+    Repeat,
+    /// A year or a full date
+    Date,
+    /// Characters not matched by any other pattern
+    Bruteforce,
+}
+
+/// A part of the password with its estimated number of guesses
+#[derive(Debug, PartialEq, Clone)]
+pub struct PatternMatch {
+    pub pattern: Pattern,
+    pub start: usize,
+    pub end: usize,
+    pub token: String,
+    pub guesses: f64,
+}
+
+/// Estimated strength of a password
+#[derive(Debug, PartialEq, Clone)]
+pub struct StrengthEstimate {
+    /// Base 10 logarithm of the estimated number of guesses
+    pub guesses_log10: f64,
+    /// 0 (too guessable) to 4 (very unguessable), using the zxcvbn thresholds
+    pub score: u8,
+    /// The matches covering the password in the cheapest way
+    pub sequence: Vec<PatternMatch>,
+    pub feedback: Vec<String>,
+}
+
+/// Returns the number of guesses needed to find the capitalization of a word
+fn uppercase_variations(token: &[char]) -> f64 {
+    let upper = token.iter().filter(|c| c.is_uppercase()).count();
+    let lower = token.iter().filter(|c| c.is_lowercase()).count();
+    let first_or_all = upper == 1 && token[0].is_uppercase() || lower == 0;
+    match upper {
+        0 => 1.0,
+        _ if first_or_all => 2.0,
+        _ => (1..=upper.min(lower)).map(|count| binomial(upper + lower, count)).sum(),
+    }
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1.0, |result, i| result * (n - i) as f64 / (i + 1) as f64)
+}
+
+fn dictionary_matches(password: &[char], matches: &mut Vec<PatternMatch>) {
+    let ranked: HashMap<&str, (usize, bool)> = ENGLISH_WORDS
+        .iter()
+        .enumerate()
+        .map(|(rank, word)| (*word, (rank + 1, false)))
+        .chain(COMMON_PASSWORDS.iter().enumerate().map(|(rank, word)| (*word, (rank + 1, true))))
+        .collect();
+
+    let lowered: Vec<char> = password.iter().flat_map(|c| c.to_lowercase()).collect();
+    if lowered.len() != password.len() {
+        return;
+    }
+    let mut candidates = vec![(lowered.clone(), false)];
+    for table in L33T_TABLES {
+        let unleet: Vec<char> = lowered.iter().map(|c| table.iter().find(|(leet, _)| leet == c).map_or(*c, |(_, plain)| *plain)).collect();
+        if unleet != lowered {
+            candidates.push((unleet, true));
+        }
+    }
+
+    for (candidate, l33t) in candidates {
+        for reversed in [false, true] {
+            for start in 0..candidate.len() {
+                for end in start + 3..=candidate.len() {
+                    let token: String = if reversed {
+                        candidate[start..end].iter().rev().collect()
+                    } else {
+                        candidate[start..end].iter().collect()
+                    };
+                    let Some((rank, common_password)) = ranked.get(token.as_str()) else { continue };
+                    let substitutions = (start..end).filter(|&i| candidate[i] != lowered[i]).count();
+                    if l33t && substitutions == 0 {
+                        continue;
+                    }
+
+                    let l33t_variations = if l33t { 2f64.powi(substitutions as i32) } else { 1.0 };
+                    let reversed_variations = if reversed { 2.0 } else { 1.0 };
+                    matches.push(PatternMatch {
+                        pattern: Pattern::Dictionary { word: token, rank: *rank, common_password: *common_password, l33t, reversed },
+                        start,
+                        end,
+                        token: password[start..end].iter().collect(),
+                        guesses: *rank as f64 * uppercase_variations(&password[start..end]) * l33t_variations * reversed_variations,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Returns the row, column and shift state of a key
+fn key_position(c: char) -> Option<(i32, i32, bool)> {
+    KEYBOARD_ROWS.iter().enumerate().find_map(|(shifted, rows)| {
+        rows.iter().enumerate().find_map(|(row, keys)| keys.chars().position(|key| key == c).map(|column| (row as i32, column as i32, shifted == 1)))
+    })
+}
+
+/// Checks whether the keys are neighbours on the staggered QWERTY layout
+fn adjacent_keys(first: char, second: char) -> bool {
+    match (key_position(first), key_position(second)) {
+        (Some((row, column, _)), Some((next_row, next_column, _))) => match next_row - row {
+            0 => (next_column - column).abs() == 1,
+            -1 => next_column == column || next_column == column + 1,
+            1 => next_column == column || next_column == column - 1,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn spatial_matches(password: &[char], matches: &mut Vec<PatternMatch>) {
+    let mut start = 0;
+    while start < password.len() {
+        let mut end = start + 1;
+        while end < password.len() && adjacent_keys(password[end - 1], password[end]) {
+            end += 1;
+        }
+        if end - start >= 3 {
+            let shifted = password[start..end].iter().filter(|c| key_position(**c).is_some_and(|(_, _, shifted)| shifted)).count();
+            let shift_variations = if shifted > 0 { 2.0 } else { 1.0 };
+            matches.push(PatternMatch {
+                pattern: Pattern::Spatial,
+                start,
+                end,
+                token: password[start..end].iter().collect(),
+                guesses: KEYBOARD_STARTING_POSITIONS * KEYBOARD_AVERAGE_DEGREE.powi((end - start - 1) as i32) * shift_variations,
+            });
+        }
+        start = end;
+    }
+}
+
+fn sequence_matches(password: &[char], matches: &mut Vec<PatternMatch>) {
+    let mut start = 0;
+    while start + 1 < password.len() {
+        let delta = password[start + 1] as i32 - password[start] as i32;
+        let mut end = start + 2;
+        while end < password.len() && password[end] as i32 - password[end - 1] as i32 == delta {
+            end += 1;
+        }
+        if end - start >= 3 && delta != 0 && delta.abs() <= 5 {
+            let first = password[start];
+            let base = if matches!(first, 'a' | 'A' | 'z' | 'Z' | '0' | '1' | '9') {
+                4.0
+            } else if first.is_ascii_digit() {
+                10.0
+            } else {
+                26.0
+            };
+            let direction = if delta < 0 { 2.0 } else { 1.0 };
+            matches.push(PatternMatch {
+                pattern: Pattern::Sequence,
+                start,
+                end,
+                token: password[start..end].iter().collect(),
+                guesses: base * (end - start) as f64 * direction,
+            });
+            start = end - 1;
+        } else {
+            start += 1;
+        }
+    }
+}
+
+fn repeat_matches(password: &[char], matches: &mut Vec<PatternMatch>) {
+    for start in 0..password.len() {
+        for period in 1..=(password.len() - start) / 2 {
+            let chunk = &password[start..start + period];
+            let repeats = password[start..].chunks(period).take_while(|part| *part == chunk).count();
+            if repeats < 2 || (period == 1 && repeats < 3) {
+                continue;
+            }
+            let end = start + period * repeats;
+            let base_guesses = 10f64.powf(estimate_chars(chunk).guesses_log10);
+            matches.push(PatternMatch {
+                pattern: Pattern::Repeat,
+                start,
+                end,
+                token: password[start..end].iter().collect(),
+                guesses: base_guesses * repeats as f64,
+            });
+        }
+    }
+}
+
+/// Returns the current year from the system clock, years close to it are the easiest to guess
+fn reference_year() -> i32 {
+    let seconds = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or_default();
+    1970 + (seconds as f64 / (365.2425 * 86_400.0)) as i32
+}
+
+/// Reads a year written with 2 or 4 digits
+fn parse_year(digits: &str) -> Option<i32> {
+    let year: i32 = digits.parse().ok()?;
+    match digits.len() {
+        2 if year > 50 => Some(1900 + year),
+        2 => Some(2000 + year),
+        4 if (1900..=2050).contains(&year) => Some(year),
+        _ => None,
+    }
+}
+
+/// Checks whether the parts form a date, in day-month-year, month-day-year or year-month-day order
+fn is_date(parts: &[&str]) -> Option<i32> {
+    let valid = |day: &str, month: &str, year: &str| -> Option<i32> {
+        let (day, month): (u32, u32) = (day.parse().ok()?, month.parse().ok()?);
+        ((1..=31).contains(&day) && (1..=12).contains(&month)).then_some(())?;
+        parse_year(year)
+    };
+    match parts {
+        [first, second, third] => valid(first, second, third)
+            .or_else(|| valid(second, first, third))
+            .or_else(|| valid(third, second, first)),
+        _ => None,
+    }
+}
+
+fn date_matches(password: &[char], matches: &mut Vec<PatternMatch>) {
+    let reference_year = reference_year();
+    let year_guesses = |year: i32| (year - reference_year).abs().max(MIN_YEAR_SPACE) as f64;
+
+    for start in 0..password.len() {
+        for end in start + 4..=(start + 10).min(password.len()) {
+            let token: String = password[start..end].iter().collect();
+            let separators: Vec<char> = token.chars().filter(|c| !c.is_ascii_digit()).collect();
+
+            let date = if separators.is_empty() {
+                match token.len() {
+                    4 => parse_year(&token).map(|year| (year, false, false)),
+                    6 | 8 => [(2, 2), (4, 2), (2, 4)]
+                        .iter()
+                        .filter(|(first, second)| first + second < token.len())
+                        .find_map(|(first, second)| is_date(&[&token[..*first], &token[*first..first + second], &token[first + second..]]))
+                        .map(|year| (year, true, false)),
+                    _ => None,
+                }
+            } else if separators.len() == 2 && separators[0] == separators[1] && "/-. _".contains(separators[0]) {
+                let parts: Vec<&str> = token.split(separators[0]).collect();
+                is_date(&parts).map(|year| (year, true, true))
+            } else {
+                None
+            };
+
+            if let Some((year, full_date, separated)) = date {
+                let days = if full_date { 365.0 } else { 1.0 };
+                let separator_variations = if separated { 4.0 } else { 1.0 };
+                matches.push(PatternMatch {
+                    pattern: Pattern::Date,
+                    start,
+                    end,
+                    token,
+                    guesses: year_guesses(year) * days * separator_variations,
+                });
+            }
+        }
+    }
+}
+
+/// Finds the sequence of matches and brute force segments with the fewest guesses
+fn minimum_guesses(password: &[char], matches: &[PatternMatch]) -> (f64, Vec<PatternMatch>) {
+    let length = password.len();
+    if length == 0 {
+        return (0.0, Vec::new());
+    }
+
+    // best[end][count] is the cheapest covering of password[..end] with count parts, in log10 guesses
+    let mut best: Vec<Vec<Option<(f64, PatternMatch)>>> = vec![vec![None; length + 1]; length + 1];
+    let mut candidates: Vec<PatternMatch> = matches.to_vec();
+    for start in 0..length {
+        for end in start + 1..=length {
+            candidates.push(PatternMatch {
+                pattern: Pattern::Bruteforce,
+                start,
+                end,
+                token: password[start..end].iter().collect(),
+                guesses: BRUTEFORCE_CARDINALITY.powi((end - start) as i32),
+            });
+        }
+    }
+
+    for end in 1..=length {
+        for candidate in candidates.iter().filter(|candidate| candidate.end == end) {
+            let minimum = if end - candidate.start == 1 { MIN_GUESSES_SINGLE_CHAR } else { MIN_GUESSES_MULTI_CHAR };
+            let guesses = candidate.guesses.max(minimum).log10();
+            for count in 1..=end {
+                let previous = match (candidate.start, count) {
+                    (0, 1) => 0.0,
+                    (0, _) | (_, 1) => continue,
+                    (start, count) => match &best[start][count - 1] {
+                        Some((previous, _)) => *previous,
+                        None => continue,
+                    },
+                };
+                let total = previous + guesses;
+                if best[end][count].as_ref().is_none_or(|(current, _)| total < *current) {
+                    best[end][count] = Some((total, candidate.clone()));
+                }
+            }
+        }
+    }
+
+    // The attacker also has to find the number and order of the parts
+    let log10_factorial = |count: usize| (1..=count).map(|i| (i as f64).log10()).sum::<f64>();
+    let (count, total) = (1..=length)
+        .filter_map(|count| best[length][count].as_ref().map(|(total, _)| (count, total + log10_factorial(count))))
+        .min_by(|first, second| first.1.total_cmp(&second.1))
+        .unwrap_or((1, length as f64));
+
+    let mut sequence = Vec::new();
+    let (mut end, mut remaining) = (length, count);
+    while remaining > 0 {
+        let Some((_, part)) = &best[end][remaining] else { break };
+        end = part.start;
+        remaining -= 1;
+        sequence.push(part.clone());
+    }
+    sequence.reverse();
+    (total, sequence)
+}
+
+/// Maps the number of guesses to the zxcvbn 0 to 4 score
+fn score(guesses_log10: f64) -> u8 {
+    match guesses_log10 {
+        log10 if log10 < 3.0 => 0,
+        log10 if log10 < 6.0 => 1,
+        log10 if log10 < 8.0 => 2,
+        log10 if log10 < 10.0 => 3,
+        _ => 4,
+    }
+}
+
+/// Explains what makes the password weak, from the part that is the easiest to guess
+fn feedback(score: u8, sequence: &[PatternMatch]) -> Vec<String> {
+    if score >= RECOMMENDED_SCORE {
+        return Vec::new();
+    }
+
+    let mut feedback = Vec::new();
+    let weakest = sequence.iter().filter(|part| part.pattern != Pattern::Bruteforce).max_by_key(|part| part.end - part.start);
+    if let Some(part) = weakest {
+        let warning = match &part.pattern {
+            Pattern::Dictionary { common_password: true, .. } if sequence.len() == 1 => "This is a very common password.",
+            Pattern::Dictionary { common_password: true, .. } => "This is similar to a commonly used password.",
+            Pattern::Dictionary { l33t: true, .. } => "Predictable substitutions like '@' instead of 'a' don't help very much.",
+            Pattern::Dictionary { reversed: true, .. } => "Reversed words aren't much harder to guess.",
+            Pattern::Dictionary { .. } if sequence.len() == 1 => "A word by itself is easy to guess.",
+            Pattern::Dictionary { .. } => "Common words are easy to guess.",
+            Pattern::Spatial => "Straight rows and short patterns of keys are easy to guess.",
+            Pattern::Sequence => "Sequences like abc or 6543 are easy to guess.",
+            Pattern::Repeat => "Repeats like aaa or abcabc are easy to guess.",
+            Pattern::Date => "Dates and years are often easy to guess.",
+            Pattern::Bruteforce => "",
+        };
+        feedback.push(warning.to_string());
+    }
+    feedback.push("Add another word or two. Uncommon words are better.".to_string());
+    feedback
+}
+
+fn estimate_chars(password: &[char]) -> StrengthEstimate {
+    let mut matches = Vec::new();
+    dictionary_matches(password, &mut matches);
+    spatial_matches(password, &mut matches);
+    sequence_matches(password, &mut matches);
+    date_matches(password, &mut matches);
+    if password.len() <= 64 {
+        repeat_matches(password, &mut matches);
+    }
+
+    let (guesses_log10, sequence) = minimum_guesses(password, &matches);
+    let score = score(guesses_log10);
+    StrengthEstimate { guesses_log10, score, feedback: feedback(score, &sequence), sequence }
+}
+
+/// Estimates how many guesses an attacker needs to find the password.
+/// Only the first `MAX_ANALYZED_LENGTH` characters are matched, the rest is scored as brute force.
+pub fn estimate_strength(password: &str) -> StrengthEstimate {
+    let password: Vec<char> = password.chars().collect();
+    if password.len() <= MAX_ANALYZED_LENGTH {
+        return estimate_chars(&password);
+    }
+
+    let mut estimate = estimate_chars(&password[..MAX_ANALYZED_LENGTH]);
+    let rest = &password[MAX_ANALYZED_LENGTH..];
+    estimate.guesses_log10 += rest.len() as f64 * BRUTEFORCE_CARDINALITY.log10();
+    estimate.sequence.push(PatternMatch {
+        pattern: Pattern::Bruteforce,
+        start: MAX_ANALYZED_LENGTH,
+        end: password.len(),
+        token: rest.iter().collect(),
+        guesses: BRUTEFORCE_CARDINALITY.powi(rest.len().min(i32::MAX as usize) as i32),
+    });
+    estimate.score = score(estimate.guesses_log10);
+    estimate.feedback = feedback(estimate.score, &estimate.sequence);
+    estimate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_common_passwords_are_weak() {
+        for password in ["password", "123456", "qwerty", "iloveyou", "Password1", "p4ssw0rd", "drowssap"] {
+            let estimate = estimate_strength(password);
+            assert!(estimate.score <= 1, "{} {:?}", password, estimate);
+            assert!(!estimate.feedback.is_empty(), "{}", password);
+        }
+        assert_eq!(estimate_strength("").score, 0);
+    }
+
+    #[test]
+    fn test_patterns_are_detected() {
+        let patterns = [
+            ("ertyuiop", Pattern::Spatial),
+            ("zxcvfr", Pattern::Spatial),
+            ("abcdefgh", Pattern::Sequence),
+            ("97531", Pattern::Sequence),
+            ("aaaaaaa", Pattern::Repeat),
+            ("xqzxqzxqz", Pattern::Repeat),
+            ("1990", Pattern::Date),
+            ("12/05/1990", Pattern::Date),
+            ("19900512", Pattern::Date),
+        ];
+        for (password, pattern) in patterns {
+            let estimate = estimate_strength(password);
+            assert_eq!(estimate.sequence.len(), 1, "{} {:?}", password, estimate.sequence);
+            assert_eq!(estimate.sequence[0].pattern, pattern, "{}", password);
+            assert!(estimate.score <= 2, "{} {:?}", password, estimate);
+        }
+    }
+
+    #[test]
+    fn test_passphrases_and_random_passwords_are_strong() {
+        assert!(estimate_strength("correct horse battery staple").score >= 3);
+        assert_eq!(estimate_strength("tK9#qL2vX!m7Zp").score, 4);
+        assert!(estimate_strength("tK9#qL2vX!m7Zp").feedback.is_empty());
+    }
+
+    #[test]
+    fn test_long_passwords_are_truncated() {
+        let long = "password".repeat(200);
+        let start = std::time::Instant::now();
+        let estimate = estimate_strength(&long);
+        assert!(start.elapsed() < std::time::Duration::from_secs(2));
+        assert_eq!(estimate.score, 4);
+        let rest = estimate.sequence.last().unwrap();
+        assert_eq!((rest.pattern.clone(), rest.start, rest.end), (Pattern::Bruteforce, MAX_ANALYZED_LENGTH, long.len()));
+        assert!(estimate.guesses_log10 > estimate_strength(&long[..MAX_ANALYZED_LENGTH]).guesses_log10 + 1499.0);
+    }
+
+    #[test]
+    fn test_l33t_and_capitalization_add_few_guesses() {
+        let plain = estimate_strength("chocolate").guesses_log10;
+        let capitalized = estimate_strength("Chocolate").guesses_log10;
+        let l33t = estimate_strength("ch0c0l4te").guesses_log10;
+        assert!(capitalized > plain && capitalized < plain + 1.0);
+        assert!(l33t > plain && l33t < plain + 1.5);
+    }
+}
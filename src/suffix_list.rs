@@ -0,0 +1,251 @@
+//! Public suffix lists used to strip hosts to their registrable domain.
+//! `SuffixList::Builtin` is the list compiled into the `psl` crate, including its private section,
+//! so pages under hosting suffixes such as `github.io` keep their own host.
+//! A newer `public_suffix_list.dat` can be loaded from disk, with or without the private section.
+//! Profile stores record the version of the list in `ProfileStore::suffix_list_version`, which is copied to
+//! `Config::suffix_list_version` when exporting, and `check_suffix_list` reports when the list in use differs,
+//! as updated lists can change the passwords of some hosts.
+//!
+//! # Examples
+//! ```
+//! use kg_passgen::suffix_list::{PublicSuffixList, SuffixList};
+//! let dat = "// VERSION: 2025-01-01\n// ===BEGIN ICANN DOMAINS===\ncom\nco.uk\n// ===BEGIN PRIVATE DOMAINS===\ngithub.io\n";
+//!
+//! let list = SuffixList::Loaded(PublicSuffixList::parse(dat, false));
+//! assert_eq!(list.version(), "2025-01-01");
+//! assert_eq!(list.domain("user.github.io"), Some("github.io".to_string()));
+//! assert_eq!(SuffixList::Builtin.domain("user.github.io"), Some("user.github.io".to_string()));
+//! ```
+
+use core::fmt;
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::url_helper::normalize_host;
+
+/// Version stamp of the list compiled into the `psl` dependency, which is pinned in Cargo.toml
+pub const BUILTIN_VERSION: &str = "psl-2.1.175";
+
+const PRIVATE_SECTION: &str = "===BEGIN PRIVATE DOMAINS===";
+
+/// Custom error type for profiles recorded with a different public suffix list
+#[derive(Debug, Clone)]
+pub struct SuffixListMismatchError {
+    pub recorded: String,
+    pub current: String,
+}
+
+impl fmt::Display for SuffixListMismatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Profile was recorded with public suffix list {} but {} is in use, some passwords may differ", self.recorded, self.current)
+    }
+}
+
+/// Rules of a `public_suffix_list.dat` file, hosts are stored in punycode
+#[derive(PartialEq, Clone, Default)]
+pub struct PublicSuffixList {
+    pub version: String,
+    pub include_private: bool,
+    rules: HashSet<String>,
+    wildcards: HashSet<String>,
+    exceptions: HashSet<String>,
+}
+
+impl fmt::Debug for PublicSuffixList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PublicSuffixList")
+            .field("version", &self.version)
+            .field("include_private", &self.include_private)
+            .field("rules", &(self.rules.len() + self.wildcards.len() + self.exceptions.len()))
+            .finish()
+    }
+}
+
+impl PublicSuffixList {
+    /// Parses the contents of a `public_suffix_list.dat` file.
+    /// The version is read from the `// VERSION:` header, rules of the private section are skipped unless included.
+    pub fn parse(contents: &str, include_private: bool) -> Self {
+        let mut list = PublicSuffixList { include_private, ..Default::default() };
+        for line in contents.lines() {
+            let line = line.trim();
+            if let Some(comment) = line.strip_prefix("//") {
+                let comment = comment.trim();
+                if let Some(version) = comment.strip_prefix("VERSION:") {
+                    list.version = version.trim().to_string();
+                } else if comment.starts_with(PRIVATE_SECTION) && !include_private {
+                    break;
+                }
+                continue;
+            }
+
+            let Some(rule) = line.split_whitespace().next() else { continue };
+            if let Some(exception) = rule.strip_prefix('!') {
+                list.exceptions.insert(normalize_host(exception));
+            } else if let Some(wildcard) = rule.strip_prefix("*.") {
+                list.wildcards.insert(normalize_host(wildcard));
+            } else {
+                list.rules.insert(normalize_host(rule));
+            }
+        }
+        if list.version.is_empty() {
+            list.version = "unknown".to_string();
+        }
+        list
+    }
+
+    /// Reads and parses the `public_suffix_list.dat` file at the path
+    pub fn load(path: impl AsRef<Path>, include_private: bool) -> std::io::Result<Self> {
+        Ok(PublicSuffixList::parse(&std::fs::read_to_string(path)?, include_private))
+    }
+
+    /// Returns the index of the first label of the public suffix.
+    /// Exception rules prevail, then the longest matching rule, then the implicit `*` rule.
+    fn suffix_start(&self, labels: &[&str]) -> usize {
+        let suffix_at = |index: usize| labels[index..].join(".");
+
+        let exception = (0..labels.len()).find(|&index| self.exceptions.contains(&suffix_at(index))).map(|index| index + 1);
+        let matched = (0..labels.len()).find(|&index| {
+            self.rules.contains(&suffix_at(index)) || (index + 1 < labels.len() && self.wildcards.contains(&suffix_at(index + 1)))
+        });
+        exception.or(matched).unwrap_or(labels.len() - 1)
+    }
+
+    /// Returns the public suffix of a normalized host
+    pub fn suffix(&self, host: &str) -> String {
+        let labels: Vec<&str> = host.split('.').collect();
+        labels[self.suffix_start(&labels)..].join(".")
+    }
+
+    /// Returns the registrable domain of a normalized host, `None` when the host is itself a public suffix
+    pub fn domain(&self, host: &str) -> Option<String> {
+        let labels: Vec<&str> = host.split('.').collect();
+        match self.suffix_start(&labels) {
+            0 => None,
+            start => Some(labels[start - 1..].join(".")),
+        }
+    }
+}
+
+/// The public suffix list used to strip subdomains
+#[derive(Debug, PartialEq, Clone)]
+pub enum SuffixList {
+    /// The list compiled into the `psl` crate, including the private section
+    Builtin,
+    /// A list loaded from a `public_suffix_list.dat` file
+    Loaded(PublicSuffixList),
+}
+
+impl SuffixList {
+    /// Returns the version stamp recorded in profiles.
+    /// Loaded lists including the private section are stamped `<version>+private`, as the section changes hosts such as `user.github.io`.
+    pub fn version(&self) -> String {
+        match self {
+            SuffixList::Builtin => BUILTIN_VERSION.to_string(),
+            SuffixList::Loaded(list) if list.include_private => format!("{}+private", list.version),
+            SuffixList::Loaded(list) => list.version.clone(),
+        }
+    }
+
+    /// Returns the public suffix of a normalized host
+    pub fn suffix(&self, host: &str) -> Option<String> {
+        match self {
+            SuffixList::Builtin => psl::suffix_str(host).map(|suffix| suffix.to_string()),
+            SuffixList::Loaded(list) => Some(list.suffix(host)),
+        }
+    }
+
+    /// Returns the registrable domain of a normalized host, `None` when the host is itself a public suffix
+    pub fn domain(&self, host: &str) -> Option<String> {
+        match self {
+            SuffixList::Builtin => psl::domain_str(host).map(|domain| domain.to_string()),
+            SuffixList::Loaded(list) => list.domain(host),
+        }
+    }
+}
+
+/// Checks that the list in use matches the version recorded in the profile.
+/// Profiles without a recorded version always pass.
+/// # Examples
+/// ```
+/// use kg_passgen::config::Config;
+/// use kg_passgen::suffix_list::{check_suffix_list, BUILTIN_VERSION};
+/// assert!(check_suffix_list(&Config::KGPG).is_ok());
+/// assert!(check_suffix_list(&Config::KGPG.with_suffix_list_version(BUILTIN_VERSION)).is_ok());
+/// assert!(check_suffix_list(&Config::KGPG.with_suffix_list_version("2020-01-01")).is_err());
+/// ```
+pub fn check_suffix_list(config: &Config) -> Result<(), SuffixListMismatchError> {
+    let current = config.suffix_list.version();
+    if config.suffix_list_version.is_empty() || config.suffix_list_version == current {
+        Ok(())
+    } else {
+        Err(SuffixListMismatchError { recorded: config.suffix_list_version.clone(), current })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAT: &str = "// This Source Code Form is subject to the terms of the Mozilla Public License
+// VERSION: 2025-08-11_10-11-48_UTC
+// ===BEGIN ICANN DOMAINS===
+com
+uk
+co.uk
+*.ck
+!www.ck
+// Unicode rules are stored as punycode
+公司.cn
+// ===END ICANN DOMAINS===
+// ===BEGIN PRIVATE DOMAINS===
+github.io
+herokuapp.com
+// ===END PRIVATE DOMAINS===
+";
+
+    #[test]
+    fn test_parse_reads_version_and_rules() {
+        let list = PublicSuffixList::parse(DAT, true);
+        assert_eq!(list.version, "2025-08-11_10-11-48_UTC");
+        assert_eq!(list.domain("a.b.example.co.uk"), Some("example.co.uk".to_string()));
+        assert_eq!(list.domain("sub.example.com"), Some("example.com".to_string()));
+        assert_eq!(list.domain("co.uk"), None);
+        assert_eq!(list.suffix("a.b.example.co.uk"), "co.uk");
+        assert_eq!(list.domain("example.xn--55qx5d.cn"), Some("example.xn--55qx5d.cn".to_string()));
+        assert_eq!(list.domain("a.b.unlisted"), Some("b.unlisted".to_string()));
+        assert_eq!(PublicSuffixList::parse("com\n", true).version, "unknown");
+    }
+
+    #[test]
+    fn test_wildcard_and_exception_rules() {
+        let list = PublicSuffixList::parse(DAT, false);
+        assert_eq!(list.domain("a.b.example.ck"), Some("b.example.ck".to_string()));
+        assert_eq!(list.domain("example.ck"), None);
+        assert_eq!(list.domain("a.www.ck"), Some("www.ck".to_string()));
+    }
+
+    #[test]
+    fn test_private_section_is_optional() {
+        let with_private = PublicSuffixList::parse(DAT, true);
+        let without_private = PublicSuffixList::parse(DAT, false);
+        assert_eq!(with_private.domain("page.user.github.io"), Some("user.github.io".to_string()));
+        assert_eq!(without_private.domain("page.user.github.io"), Some("github.io".to_string()));
+        assert_eq!(SuffixList::Builtin.domain("page.user.github.io"), Some("user.github.io".to_string()));
+        assert_eq!(SuffixList::Builtin.domain("app.herokuapp.com"), Some("app.herokuapp.com".to_string()));
+    }
+
+    #[test]
+    fn test_check_suffix_list_reports_mismatch() {
+        let list = SuffixList::Loaded(PublicSuffixList::parse(DAT, true));
+        let config = Config::default().with_suffix_list(list).with_suffix_list_version(BUILTIN_VERSION);
+        let error = check_suffix_list(&config).unwrap_err();
+        assert_eq!(error.recorded, BUILTIN_VERSION);
+        assert_eq!(error.current, "2025-08-11_10-11-48_UTC+private");
+        let config = config.with_suffix_list_version("2025-08-11_10-11-48_UTC+private");
+        assert!(check_suffix_list(&config).is_ok());
+
+        let without_private = config.with_suffix_list(SuffixList::Loaded(PublicSuffixList::parse(DAT, false)));
+        assert_eq!(check_suffix_list(&without_private).unwrap_err().current, "2025-08-11_10-11-48_UTC");
+    }
+}
@@ -0,0 +1,236 @@
+//! Password templates for sites that require a fixed format, such as `AAAA-9999-aaaa`.
+//! `a` is a lowercase letter, `A` an uppercase letter, `9` a digit and `s` a symbol,
+//! any other character is kept as is and `\` keeps the next character as is, so `\s` is a literal `s`.
+//! `{n}` repeats the previous character n times, so `A{4}-9{4}` is the same as `AAAA-9999`.
+//! Set on `Config::template`, hop based generators fill the slots from the bytes of the last digest.
+//!
+//! # Examples
+//! ```
+//! use kg_passgen::config::Config;
+//! use kg_passgen::generator::generate_password;
+//! use kg_passgen::template::Template;
+//! let template = Template::parse("A{4}-9{4}-a{4}").unwrap();
+//! assert_eq!(template.len(), 14);
+//!
+//! let config = Config::KGPG.with_template(template.clone());
+//! let password = generate_password("https://example.com", "my_master_password", &config).unwrap();
+//! assert!(template.matches(&password));
+//! assert_eq!(password, "PLCW-1696-pxdq");
+//!
+//! assert!(Template::parse("a{0}").is_err());
+//! assert!(Template::parse("BCD-").is_err());
+//! ```
+
+use core::fmt;
+use std::str::FromStr;
+
+use crate::config::HashAlgorithm;
+use crate::generator::modulo_entropy_bits;
+
+const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+/// The symbols allowed in KGPG and SGP passwords
+const SYMBOLS: &str = "!#$%&*?@^";
+
+/// Random slots are filled with one byte each, so a SHA512 digest fills at most 64, see `Template::max_slots`
+pub const MAX_SLOTS: usize = 64;
+
+/// Custom error type for templates that could not be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateError(pub String);
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid template: {}", self.0)
+    }
+}
+
+/// One character of a template
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Slot {
+    Lowercase,
+    Uppercase,
+    Digit,
+    Symbol,
+    Literal(char),
+}
+
+impl Slot {
+    /// Returns the characters of a random slot, `None` for literals
+    pub fn characters(&self) -> Option<&'static str> {
+        match self {
+            Slot::Lowercase => Some(LOWERCASE),
+            Slot::Uppercase => Some(UPPERCASE),
+            Slot::Digit => Some(DIGITS),
+            Slot::Symbol => Some(SYMBOLS),
+            Slot::Literal(_) => None,
+        }
+    }
+}
+
+/// A parsed template with at least one and at most `MAX_SLOTS` random slots
+#[derive(Debug, PartialEq, Clone)]
+pub struct Template {
+    source: String,
+    slots: Vec<Slot>,
+}
+
+impl Template {
+    /// Parses the template, failing on a trailing `\`, counts outside 1 to `MAX_SLOTS`, unmatched braces or templates without random slots
+    pub fn parse(source: &str) -> Result<Template, TemplateError> {
+        let mut slots = Vec::new();
+        let mut chars = source.chars();
+        while let Some(c) = chars.next() {
+            let slot = match c {
+                'a' => Slot::Lowercase,
+                'A' => Slot::Uppercase,
+                '9' => Slot::Digit,
+                's' => Slot::Symbol,
+                '\\' => Slot::Literal(chars.next().ok_or_else(|| TemplateError("trailing \\".to_string()))?),
+                '{' => {
+                    let (count, _) = chars.as_str().split_once('}').ok_or_else(|| TemplateError("{ without }".to_string()))?;
+                    let count = count.to_string();
+                    chars.nth(count.chars().count());
+                    let previous = *slots.last().ok_or_else(|| TemplateError("{ without a character to repeat".to_string()))?;
+                    let count: usize = count.parse().ok().filter(|count| (1..=MAX_SLOTS).contains(count))
+                        .ok_or_else(|| TemplateError(format!("invalid count {{{}}}, counts go from 1 to {}", count, MAX_SLOTS)))?;
+                    slots.extend(std::iter::repeat_n(previous, count - 1));
+                    continue;
+                },
+                '}' => return Err(TemplateError("} without {".to_string())),
+                _ => Slot::Literal(c),
+            };
+            slots.push(slot);
+        }
+
+        let template = Template { source: source.to_string(), slots };
+        if template.random_slots() == 0 {
+            return Err(TemplateError("no a, A, 9 or s slot".to_string()));
+        }
+        if template.random_slots() > MAX_SLOTS {
+            return Err(TemplateError(format!("more than {} random slots", MAX_SLOTS)));
+        }
+        Ok(template)
+    }
+
+    /// Number of random slots the digest of the hash algorithm can fill
+    pub fn max_slots(hash_algorithm: &HashAlgorithm) -> usize {
+        match hash_algorithm {
+            HashAlgorithm::MD5 => 16,
+            HashAlgorithm::SHA512 => MAX_SLOTS,
+        }
+    }
+
+    /// Number of `a`, `A`, `9` and `s` slots
+    pub fn random_slots(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.characters().is_some()).count()
+    }
+
+    /// Checks that the digest of the hash algorithm fills every random slot
+    pub fn check_hash(&self, hash_algorithm: &HashAlgorithm) -> Result<(), TemplateError> {
+        let max_slots = Template::max_slots(hash_algorithm);
+        match self.random_slots() {
+            slots if slots > max_slots => Err(TemplateError(format!("{} random slots but {:?} fills at most {}", slots, hash_algorithm, max_slots))),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn slots(&self) -> &[Slot] {
+        &self.slots
+    }
+
+    /// Number of characters of the passwords
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Fills every random slot with the next byte modulo the size of its set, `None` when there are fewer bytes than random slots.
+    /// Sets whose size does not divide 256 favour their first characters slightly, see `entropy_bits`.
+    pub fn fill(&self, bytes: &[u8]) -> Option<String> {
+        let mut bytes = bytes.iter();
+        self.slots.iter().map(|slot| match slot.characters() {
+            Some(characters) => {
+                let byte = *bytes.next()? as usize;
+                characters.chars().nth(byte % characters.len())
+            },
+            None => match slot {
+                Slot::Literal(c) => Some(*c),
+                _ => None,
+            },
+        }).collect()
+    }
+
+    /// Checks that every character of the password matches its slot
+    pub fn matches(&self, password: &str) -> bool {
+        password.chars().count() == self.slots.len() && password.chars().zip(&self.slots).all(|(c, slot)| match slot {
+            Slot::Literal(literal) => c == *literal,
+            _ => slot.characters().is_some_and(|characters| characters.contains(c)),
+        })
+    }
+
+    /// Estimates the entropy in bits of the passwords from every random slot,
+    /// accounting for the bias of taking a byte modulo the size of the set
+    pub fn entropy_bits(&self) -> f64 {
+        self.slots.iter().filter_map(Slot::characters).map(|characters| modulo_entropy_bits(256, characters.len())).sum()
+    }
+}
+
+impl FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Template::parse(source)
+    }
+}
+
+impl fmt::Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_expands_counts_and_escapes() {
+        let template = Template::parse("A{2}\\s-9").unwrap();
+        assert_eq!(template.slots(), [Slot::Uppercase, Slot::Uppercase, Slot::Literal('s'), Slot::Literal('-'), Slot::Digit]);
+        assert_eq!(template.slots(), "AA\\s-9".parse::<Template>().unwrap().slots());
+        assert_eq!(template.to_string(), "A{2}\\s-9");
+        assert_eq!(Template::parse("-{3}a").unwrap().slots()[..3], [Slot::Literal('-'); 3]);
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_templates() {
+        for source in ["", "---", "\\a", "a\\", "{2}", "a{x}", "a{0}", "a{2", "a}", "a{65}", "-{100}a"] {
+            assert!(Template::parse(source).is_err(), "{}", source);
+        }
+        assert!(Template::parse("a{64}").is_ok());
+        assert_eq!(Template::parse("-{64}a").unwrap().len(), 65);
+    }
+
+    #[test]
+    fn test_fill_uses_one_byte_per_random_slot() {
+        let template = Template::parse("a-A9s").unwrap();
+        assert_eq!(template.fill(&[0, 27, 13, 0]), Some("a-B3!".to_string()));
+        assert_eq!(template.fill(&[0, 27, 13]), None);
+        assert!(template.matches("a-B3!"));
+        assert!(!template.matches("a_B3!"));
+        assert!(!template.matches("a-B3!x"));
+        let unbiased = 2.0 * 26f64.log2() + 10f64.log2() + 9f64.log2();
+        assert!(template.entropy_bits() < unbiased && template.entropy_bits() > unbiased - 0.01);
+        assert_eq!(Template::parse("9{2}").unwrap().fill(&[9, 255]), Some("95".to_string()));
+
+        let sixteen = Template::parse("a{16}").unwrap();
+        assert!(sixteen.check_hash(&HashAlgorithm::MD5).is_ok());
+        assert!(Template::parse("a{17}-").unwrap().check_hash(&HashAlgorithm::MD5).is_err());
+        assert!(Template::parse("a{17}").unwrap().check_hash(&HashAlgorithm::SHA512).is_ok());
+    }
+}
@@ -0,0 +1,144 @@
+//! Derives unlinkable per-site usernames and email aliases from the master password and the service URL.
+//! Uses the `generator::derive_bytes` labels `username:word-pair`, `username:hex-tag` and `username:plus-address`.
+//!
+//! # Examples
+//! ```
+//! use kg_passgen::config::Config;
+//! use kg_passgen::username::{generate_username, UsernameStyle};
+//! let config = Config::default();
+//!
+//! let username = generate_username("https://example.com", "my_master_password", &UsernameStyle::WordPair, &config).unwrap();
+//! assert_eq!(username, generate_username("https://login.example.com", "my_master_password", &UsernameStyle::WordPair, &config).unwrap());
+//!
+//! let alias = generate_username("https://example.com", "my_master_password", &UsernameStyle::PlusAddress("me@mail.com".to_string()), &config).unwrap();
+//! assert!(alias.starts_with("me+") && alias.ends_with("@mail.com"));
+//! ```
+
+use core::fmt;
+
+use crate::config::Config;
+use crate::generator::derive_bytes;
+
+/// Custom error type for email addresses that cannot be plus addressed
+#[derive(Debug, Clone)]
+pub struct InvalidEmailError;
+
+impl fmt::Display for InvalidEmailError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid email address for plus addressing")
+    }
+}
+
+/// Formats of the generated username
+#[derive(Debug, PartialEq, Clone)]
+pub enum UsernameStyle {
+    /// An adjective and a noun followed by two digits, e.g. `quiet_falcon42`
+    WordPair,
+    /// `u` followed by 12 hex characters, e.g. `u3fa9c1d20b7e`
+    HexTag,
+    /// Adds a hex tag to the local part of the email address, e.g. `me+3fa9c1d2@mail.com`
+    PlusAddress(String),
+}
+
+pub(crate) const ADJECTIVES: [&str; 128] = [
+    "able", "agile", "amber", "ample", "azure", "bold", "brave", "brisk", "bright", "broad",
+    "calm", "candid", "cheery", "civil", "clever", "cosmic", "crisp", "curious", "daring", "dawn",
+    "deft", "eager", "early", "easy", "elder", "electric", "epic", "even", "fair", "fancy", "fast",
+    "fierce", "fine", "firm", "fluffy", "fond", "free", "fresh", "frosty", "gentle", "giant",
+    "glad", "golden", "grand", "great", "green", "happy", "hardy", "hazy", "hidden", "honest",
+    "humble", "icy", "ideal", "jolly", "keen", "kind", "lively", "lone", "loyal", "lucky", "lunar",
+    "mellow", "merry", "mighty", "misty", "modern", "noble", "nimble", "odd", "olive", "open",
+    "patient", "plain", "polar", "polite", "proud", "quick", "quiet", "rapid", "rare", "ready",
+    "regal", "rich", "rosy", "royal", "rustic", "safe", "sandy", "scarlet", "sharp", "shiny",
+    "silent", "silver", "simple", "sleek", "slow", "smart", "smooth", "snowy", "solar", "solid",
+    "sonic", "spare", "spry", "steady", "stellar", "still", "stony", "stout", "sunny", "super",
+    "swift", "tall", "tame", "tidy", "tiny", "tough", "tranquil", "true", "upbeat", "urban",
+    "valid", "vast", "velvet", "vivid", "warm", "wild",
+];
+
+pub(crate) const NOUNS: [&str; 128] = [
+    "acorn", "anchor", "antelope", "apple", "arrow", "aspen", "badger", "bamboo", "beacon", "bear",
+    "beaver", "birch", "bison", "breeze", "brook", "buffalo", "cactus", "canyon", "cedar",
+    "cheetah", "cliff", "cloud", "comet", "condor", "coral", "cougar", "coyote", "crane", "creek",
+    "crystal", "cypress", "delta", "dolphin", "dove", "dragon", "dune", "eagle", "echo", "elk",
+    "ember", "falcon", "fern", "finch", "fjord", "flame", "fox", "galaxy", "garnet", "gazelle",
+    "geyser", "glacier", "goose", "granite", "gull", "harbor", "hawk", "heather", "heron", "hill",
+    "horizon", "ibis", "island", "jaguar", "jasper", "kestrel", "koala", "lagoon", "lake", "lark",
+    "leopard", "lion", "lotus", "lynx", "maple", "marble", "meadow", "meteor", "mink", "moose",
+    "moss", "nebula", "newt", "oak", "ocean", "orca", "osprey", "otter", "owl", "panda", "panther",
+    "parrot", "pebble", "pelican", "pine", "planet", "plume", "pony", "prairie", "puma", "quail",
+    "quartz", "rabbit", "raven", "reef", "ridge", "river", "robin", "sage", "salmon", "sparrow",
+    "spruce", "star", "stone", "stork", "summit", "swan", "thunder", "tiger", "trout", "tulip",
+    "valley", "viper", "walrus", "willow", "wolf", "wren", "yak", "zebra",
+];
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Generates a username for the service URL in the selected style
+pub fn generate_username(url: &str, master_password: &str, style: &UsernameStyle, config: &Config) -> Result<String, InvalidEmailError> {
+    let host = crate::url_helper::get_config_host(url, config);
+
+    match style {
+        UsernameStyle::WordPair => {
+            let bytes = derive_bytes("username:word-pair", master_password, &host);
+            let adjective = ADJECTIVES[bytes[0] as usize % ADJECTIVES.len()];
+            let noun = NOUNS[bytes[1] as usize % NOUNS.len()];
+            let number = u16::from_be_bytes([bytes[2], bytes[3]]) % 100;
+            Ok(format!("{}_{}{:02}", adjective, noun, number))
+        },
+        UsernameStyle::HexTag => {
+            let bytes = derive_bytes("username:hex-tag", master_password, &host);
+            Ok(format!("u{}", to_hex(&bytes[0..6])))
+        },
+        UsernameStyle::PlusAddress(email) => {
+            let (local, domain) = match email.trim().rsplit_once('@') {
+                Some((local, domain)) if !local.is_empty() && !domain.is_empty() => (local, domain),
+                _ => return Err(InvalidEmailError),
+            };
+            let bytes = derive_bytes("username:plus-address", master_password, &host);
+            Ok(format!("{}+{}@{}", local, to_hex(&bytes[0..4]), domain))
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_username_word_pair_format() {
+        let username = generate_username("https://example.com", "master", &UsernameStyle::WordPair, &Config::default()).unwrap();
+        let (adjective, rest) = username.split_once('_').unwrap();
+        assert!(ADJECTIVES.contains(&adjective));
+        let (noun, number) = rest.split_at(rest.len() - 2);
+        assert!(NOUNS.contains(&noun));
+        assert!(number.chars().all(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn test_generate_username_differs_per_host_and_from_password() {
+        let config = Config::default();
+        let example = generate_username("https://example.com", "master", &UsernameStyle::HexTag, &config).unwrap();
+        let test = generate_username("https://test.com", "master", &UsernameStyle::HexTag, &config).unwrap();
+        assert_eq!(example.len(), 13);
+        assert_ne!(example, test);
+
+        let password = crate::generator::generate_password("https://example.com", "master", &config).unwrap();
+        assert!(!password.contains(&example[1..]));
+    }
+
+    #[test]
+    fn test_generate_username_plus_address() {
+        let style = UsernameStyle::PlusAddress("first.last@mail.example.org".to_string());
+        let alias = generate_username("https://example.com", "master", &style, &Config::default()).unwrap();
+        let (local, domain) = alias.split_once('@').unwrap();
+        assert_eq!(domain, "mail.example.org");
+        assert!(local.starts_with("first.last+"));
+        assert_eq!(local.len(), "first.last+".len() + 8);
+
+        let invalid = UsernameStyle::PlusAddress("not-an-email".to_string());
+        assert!(matches!(generate_username("https://example.com", "master", &invalid, &Config::default()), Err(InvalidEmailError)));
+    }
+}
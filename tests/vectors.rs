@@ -0,0 +1,62 @@
+//! Cross-implementation test vectors shared with the Dart/Flutter app.
+//! The vector file is versioned; any change to an expected output must bump its version.
+use kg_passgen::config::{Config, GeneratorType, HashAlgorithm};
+use kg_passgen::generator::generate_password;
+use serde_json::Value;
+
+const VECTORS: &str = include_str!("vectors/kgpassgen_v2.json");
+const VECTORS_VERSION: u64 = 2;
+
+fn parse_config(value: &Value) -> Config {
+    let generator_type = match value["generator_type"].as_str().unwrap() {
+        "KGPG" => GeneratorType::KGPG,
+        "SGP" => GeneratorType::SGP,
+        other => panic!("Unknown generator type {}", other),
+    };
+    let hash_algorithm = match value["hash_algorithm"].as_str().unwrap() {
+        "SHA512" => HashAlgorithm::SHA512,
+        "MD5" => HashAlgorithm::MD5,
+        other => panic!("Unknown hash algorithm {}", other),
+    };
+
+    Config::default()
+        .with_generator_type(generator_type)
+        .with_hash_algorithm(hash_algorithm)
+        .with_length(value["length"].as_u64().unwrap() as u8)
+        .with_hops(value["hops"].as_u64().unwrap() as u8)
+        .with_strip_subdomain(value["strip_subdomain"].as_bool().unwrap())
+        .with_include_port(value["include_port"].as_bool().unwrap_or(false))
+}
+
+#[test]
+fn test_vector_file_version() {
+    let document: Value = serde_json::from_str(VECTORS).unwrap();
+    assert_eq!(document["version"].as_u64(), Some(VECTORS_VERSION));
+}
+
+#[test]
+fn test_vectors_match_reference_outputs() {
+    let document: Value = serde_json::from_str(VECTORS).unwrap();
+    let vectors = document["vectors"].as_array().unwrap();
+    assert!(!vectors.is_empty());
+
+    for vector in vectors {
+        let name = vector["name"].as_str().unwrap();
+        let url = vector["url"].as_str().unwrap();
+        let master_password = vector["master_password"].as_str().unwrap();
+        let config = parse_config(&vector["config"]);
+        let result = generate_password(url, master_password, &config);
+
+        match vector["expected"].as_str() {
+            Some(expected) => {
+                let password = result.unwrap_or_else(|e| panic!("{}: unexpected error {}", name, e));
+                assert_eq!(password, expected, "{}", name);
+                assert_eq!(password.len(), config.length as usize, "{}", name);
+            },
+            None => {
+                assert_eq!(vector["error"].as_str(), Some("InvalidLength"), "{}", name);
+                assert!(result.is_err(), "{}", name);
+            },
+        }
+    }
+}